@@ -1,20 +1,38 @@
 use crossterm::style::Color;
 use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 
-use crate::{point::Point, RNG, ROSE, branch::LeafType};
+use crate::{Result, RNG, branch::{Branching, BranchShape, LeafType, Style, TrunkGlyphs}, config::Config, point::Point, render::Renderer};
 
 
 /// Types of bases
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum BaseType {
     LargePot,
     SmallPot,
+    RectangularPot,
+    Bowl,
+    /// Rounded pot standing on small feet
+    FootedPot,
+    /// Tall and narrow pot, made for trees hanging over the rim
+    CascadePot,
+    /// No base at all, the tree grows directly from the bottom of the screen
+    NoPot,
 }
 
+impl std::str::FromStr for BaseType {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, String> {
+        crate::parse_name(name)
+    }
+}
 
 
 /// Defines the appearance of a bonsai tree.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TreeAppearance {
     /// How far the leaves extend from the back of a branch
     pub leaf_count: usize,
@@ -26,62 +44,193 @@ pub struct TreeAppearance {
     /// Bonus for trunk width
     pub trunk_width_bonus: i16,
     /// Type of base
-    pub base: BaseType
+    pub base: BaseType,
+    pub base_color: Color,
+    /// How much the tree was scaled down to fit a small screen, 1.0 is full size
+    pub scale: f32,
+    /// Color of the trunk and the branches
+    pub trunk_color: Color,
+    /// The strings leaves are picked from, the built-in ones of `leaf_type` if empty
+    pub leaf_glyphs: Vec<String>,
+    /// Smallest and largest number of leaves at each leaf attachment point, before adding the trunk width
+    pub leaves_per_cluster: (usize, usize),
+    pub trunk_shape: BranchShape,
+    pub branch_shape: BranchShape,
+    pub branching: Branching,
+    /// The characters the trunk and the branches are drawn with
+    pub trunk_glyphs: TrunkGlyphs,
+    /// The strings fruit are picked from, only used by fruit trees
+    pub fruit_glyphs: Vec<String>,
+    pub style: Style,
+    /// The side slanting trunks lean to, cascades fall to and the wind blows to: -1 for left, 1 for right
+    pub side: i16,
 }
 
+
 impl TreeAppearance {
-    pub fn randomize(rng: &mut RNG, trunk_width: usize) -> TreeAppearance {
+    /// Picks everything that isn't fixed by `config` at random
+    pub fn randomize(rng: &mut RNG, trunk_width: usize, config: &Config) -> TreeAppearance {
         // Extra leaf size based on trunk width
-        let trunk_width_bonus = (trunk_width as f32 / 5.0 as f32).round() as i16;
+        let trunk_width_bonus = (trunk_width as f32 / 5.0).round() as i16;
 
-        let leaf_type = [
-            LeafType::Pointy,
-            LeafType::Round,
-            ].choose(rng).unwrap().clone();
+        // Pinned choices are still picked at random first, so every seed keeps the rest of its look
+        let pin = &config.pin;
+        let leaf_type = *config.leaf_types.choose(rng).unwrap();
+        let leaf_type = pin.leaf_type.unwrap_or(leaf_type);
         
-        let color_arr = match rng.gen_bool(0.05) {
-            true => vec![Color::Rgb { r: 1, g: 1, b: 1 }],
-            false => vec![Color::Green, Color::Red, Color::Yellow, ROSE],
+        let color_arr = match (rng.gen_bool(config.dark_leaf_chance), leaf_type.palette()) {
+            (true, _) => vec![Color::Rgb { r: 1, g: 1, b: 1 }],
+            (false, Some(palette)) => palette.to_vec(),
+            (false, None) => config.leaf_colors.clone(),
         };
+        let leaf_glyphs = config.leaves.get(leaf_type).to_vec();
+        let leaf_count = rng.gen_range(config.leaf_count.0..=config.leaf_count.1);
+        let leaf_color = *color_arr.choose(rng).unwrap();
+        let base = *[
+            BaseType::LargePot,
+            BaseType::SmallPot,
+            BaseType::RectangularPot,
+            BaseType::Bowl,
+            BaseType::FootedPot,
+            BaseType::CascadePot,
+            ].choose(rng).unwrap();
 
         TreeAppearance {
-            leaf_count: rng.gen_range(2..=4),
+            leaf_count: pin.leaf_count.unwrap_or(leaf_count),
             leaf_type,
-            leaf_color: *color_arr.choose(rng).unwrap(),
+            leaf_color: pin.leaf_color.unwrap_or(leaf_color),
             trunk_width,
             trunk_width_bonus,
-            base: *[BaseType::LargePot, BaseType::SmallPot].choose(rng).unwrap(),
+            base: pin.base.unwrap_or(base),
+            base_color: *config.base_colors.choose(rng).unwrap(),
+            scale: 1.0,
+            trunk_color: config.trunk_color,
+            leaf_glyphs,
+            leaves_per_cluster: config.leaves_per_cluster,
+            trunk_shape: config.trunk_shape,
+            branch_shape: config.branch_shape,
+            branching: config.branching,
+            trunk_glyphs: config.trunk_glyphs.clone(),
+            fruit_glyphs: match leaf_type {
+                LeafType::Fruit => config.fruits.clone(),
+                _ => vec![],
+            },
+            style: config.style,
+            // Only picked for the styles which need it, so the others keep their look for every seed
+            side: match config.style.is_one_sided() && rng.gen_bool(0.5) {
+                true => -1,
+                false => 1,
+            },
         }
     }
 
+    /// Builds the ASCII art of the base (plant pot). The first line is the rim,
+    /// which leaves a gap of `trunk_width` after `margin + 1` characters for the trunk to grow out of
     pub fn get_base(&self, margin: usize) -> String {
-        let mut base = String::from(" ");
+        // Width between the outer walls of the pot
+        let span = 2 * margin + self.trunk_width;
+        let mut base = String::new();
         match self.base {
+            BaseType::NoPot => {},
             BaseType::LargePot => {
-                base.push_str(&"_".repeat(margin));
-                base.push_str(&" ".repeat(self.trunk_width));
-                base.push_str(&"_".repeat(margin));
-                base.push_str("\n");
+                Self::push_rim(&mut base, margin, self.trunk_width);
 
-                base.push_str("\\");
-                base.push_str(&" ".repeat(margin - 2 + self.trunk_width));
+                base.push('\\');
+                base.push_str(&" ".repeat(span.saturating_sub(1)));
                 base.push_str("/\n");
 
                 base.push_str(" \\");
-                base.push_str(&" ".repeat(margin - 4 + self.trunk_width));
+                base.push_str(&" ".repeat(span.saturating_sub(3)));
                 base.push_str("/\n");
             },
             BaseType::SmallPot => {
-                base.push_str(&"_".repeat(margin));
-                base.push_str(&" ".repeat(self.trunk_width));
-                base.push_str(&"_".repeat(margin));
-                base.push_str("\n");
+                Self::push_rim(&mut base, margin, self.trunk_width);
 
-                base.push_str("(");
-                base.push_str(&" ".repeat(margin - 2 + self.trunk_width));
+                base.push('(');
+                base.push_str(&" ".repeat(span.saturating_sub(1)));
                 base.push_str(")\n");
-            }
+            },
+            BaseType::RectangularPot => {
+                Self::push_rim(&mut base, margin, self.trunk_width);
+
+                base.push('|');
+                base.push_str(&" ".repeat(span.saturating_sub(1)));
+                base.push_str("|\n");
+
+                base.push('|');
+                base.push_str(&"_".repeat(span.saturating_sub(1)));
+                base.push_str("|\n");
+            },
+            BaseType::Bowl => {
+                Self::push_rim(&mut base, margin, self.trunk_width);
+
+                base.push('\\');
+                base.push_str(&" ".repeat(span.saturating_sub(1)));
+                base.push_str("/\n");
+
+                base.push_str(" \\");
+                base.push_str(&"_".repeat(span.saturating_sub(3)));
+                base.push_str("/\n");
+            },
+            BaseType::FootedPot => {
+                Self::push_rim(&mut base, margin, self.trunk_width);
+
+                base.push('(');
+                base.push_str(&" ".repeat(span.saturating_sub(1)));
+                base.push_str(")\n");
+
+                base.push_str(" \\");
+                base.push_str(&"_".repeat(span.saturating_sub(3)));
+                base.push_str("/\n");
+
+                base.push_str("  ||");
+                base.push_str(&" ".repeat(span.saturating_sub(7)));
+                base.push_str("||\n");
+            },
+            BaseType::CascadePot => {
+                // Tall and narrow, so it only uses a small part of the margin
+                let pad = " ".repeat(margin.saturating_sub(2));
+                let span = 4 + self.trunk_width;
+                base.push_str(&pad);
+                Self::push_rim(&mut base, 2, self.trunk_width);
+
+                for _ in 0..3 {
+                    base.push_str(&pad);
+                    base.push('|');
+                    base.push_str(&" ".repeat(span.saturating_sub(1)));
+                    base.push_str("|\n");
+                }
+
+                base.push_str(&pad);
+                base.push('|');
+                base.push_str(&"_".repeat(span.saturating_sub(1)));
+                base.push_str("|\n");
+            },
         };
         base
     }
-}
\ No newline at end of file
+
+    /// Draws the base so that the gap in its rim sits at `trunk_start`
+    pub fn draw_base<R: Renderer + ?Sized>(&self, margin: usize, trunk_start: Point<i16>, renderer: &mut R) -> Result<()> {
+        let origin_x = trunk_start.x - margin as i16 - 1;
+        for (idx, line) in self.get_base(margin).lines().enumerate() {
+            let trimmed = line.trim_start();
+            let x = origin_x + (line.len() - trimmed.len()) as i16;
+            let y = trunk_start.y + idx as i16;
+            if x < 0 || y < 0 {
+                continue;
+            }
+            renderer.draw((x as u16, y as u16), trimmed, self.base_color)?;
+        }
+        Ok(())
+    }
+
+    /// Pushes the rim of a pot, leaving space for the trunk in the middle
+    fn push_rim(base: &mut String, margin: usize, trunk_width: usize) {
+        base.push(' ');
+        base.push_str(&"_".repeat(margin));
+        base.push_str(&" ".repeat(trunk_width));
+        base.push_str(&"_".repeat(margin));
+        base.push('\n');
+    }
+}
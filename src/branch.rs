@@ -1,13 +1,12 @@
-use std::fmt::Write;
-
-use crate::{point::Point, RNG, draw, Writer};
+use crate::{appearance::TreeAppearance, point::Point, ROSE, region::Region, RNG, render::{char_width, text_width, Renderer}, Result};
 use crossterm::style::Color;
 use rand::{Rng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
 use simple_simplex::NoiseConfig;
 
 
 /// Direction of a branch
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     Up,
     Left,
@@ -17,7 +16,8 @@ pub enum Direction {
 
 
 /// Defines how a branch looks
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct BranchShape {
     /// initial chance to loose width
     pub width_loose_chance: f32,
@@ -25,13 +25,27 @@ pub struct BranchShape {
     pub min_width_loose_chance: f32,
     /// How much percent the width chance looses
     pub width_loose_ratio: f32,
+    /// Shrinks the length of side branches and the leaf clusters for small screens, 1.0 is full size.
+    /// Not stored, every branch takes it from the appearance of its tree
+    #[serde(skip, default = "full_scale")]
+    pub scale: f32,
+    /// Number of steps after which side branches stop growing, at full size
+    pub length: i16,
+    /// Chance for each step of a side branch to climb a row
+    pub rise_chance: f64,
+    /// Chance for each step of a side branch which doesn't climb to hang down a row
+    pub droop_chance: f64,
 }
 impl BranchShape {
     pub fn default_trunk() -> Self {
         BranchShape {
             width_loose_chance: 1.0,
             min_width_loose_chance: 0.23,
-            width_loose_ratio: 0.8
+            width_loose_ratio: 0.8,
+            scale: 1.0,
+            length: 10,
+            rise_chance: 0.3,
+            droop_chance: 0.0,
         }
     }
 
@@ -39,22 +53,196 @@ impl BranchShape {
         BranchShape {
             width_loose_chance: 0.3,
             min_width_loose_chance: 0.28,
-            width_loose_ratio: 0.8
+            width_loose_ratio: 0.8,
+            scale: 1.0,
+            length: 10,
+            rise_chance: 0.3,
+            droop_chance: 0.0,
+        }
+    }
+
+    /// Scales a full size length, but never below `min`
+    fn scaled(&self, value: i16, min: i16) -> i16 {
+        ((value as f32 * self.scale).round() as i16).max(min)
+    }
+}
+
+
+fn full_scale() -> f32 {
+    1.0
+}
+
+
+/// Where and how often side branches sprout from the trunk
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Branching {
+    /// How far up the trunk has to be before branches sprout, from 0 at the bottom of the screen to 1 at the top
+    pub start: f32,
+    /// Rows between branches on the same side, in trunk widths
+    pub spacing: f32,
+    /// Scales the chance for a branch on the other side than the last one
+    pub frequency: f64,
+    /// From -1 to 1: how much branches favor the left (negative) or the right (positive) side
+    pub bias: f64,
+}
+
+impl Default for Branching {
+    fn default() -> Self {
+        Branching {
+            start: 0.35,
+            spacing: 1.0,
+            frequency: 1.0,
+            bias: 0.0,
+        }
+    }
+}
+
+
+/// The characters branches are drawn with, for each direction they grow in.
+/// The first character of a set starts every step, the rest of the step is picked at random from the whole set
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TrunkGlyphs {
+    /// Growing straight up
+    pub up: String,
+    /// Growing up and left or down and right
+    pub up_left: String,
+    /// Growing up and right or down and left
+    pub up_right: String,
+    pub left: String,
+    pub right: String,
+}
+
+impl Default for TrunkGlyphs {
+    fn default() -> Self {
+        TrunkGlyphs {
+            up: "/|\\".to_string(),
+            up_left: "\\~".to_string(),
+            up_right: "/~\\".to_string(),
+            left: "\\~-_=".to_string(),
+            right: "/~-_=".to_string(),
+        }
+    }
+}
+
+impl TrunkGlyphs {
+    /// Uses the same characters for every direction
+    pub fn uniform(glyphs: &str) -> TrunkGlyphs {
+        TrunkGlyphs {
+            up: glyphs.to_string(),
+            up_left: glyphs.to_string(),
+            up_right: glyphs.to_string(),
+            left: glyphs.to_string(),
+            right: glyphs.to_string(),
         }
     }
+
+    /// The characters of every direction
+    pub fn sets(&self) -> [&String; 5] {
+        [&self.up, &self.up_left, &self.up_right, &self.left, &self.right]
+    }
+
+    /// Whether every direction has at least one character
+    pub fn is_complete(&self) -> bool {
+        self.sets().iter().all(|set| !set.is_empty())
+    }
 }
 
 
-const POINTY_LEAVES: [&'static str; 4] = ["V", "W", "VW", "WVW"];
-const ROUND_LEAVES: [&'static str; 3] = ["&", "o", "0"];
+pub(crate) const POINTY_LEAVES: [&str; 4] = ["V", "W", "VW", "WVW"];
+pub(crate) const ROUND_LEAVES: [&str; 3] = ["&", "o", "0"];
+pub(crate) const BLOSSOM_LEAVES: [&str; 4] = ["*", "@", "**", "*@*"];
+pub(crate) const NEEDLE_LEAVES: [&str; 5] = ["\\|/", "|", "/", "\\", "\\/"];
+pub(crate) const MAPLE_LEAVES: [&str; 4] = ["M", "W", "MM", "WMW"];
+pub(crate) const FRUIT_LEAVES: [&str; 3] = ["V", "W", "&"];
+/// What fruit trees grow among their leaves
+pub(crate) const FRUITS: [&str; 3] = ["o", "@", "O"];
+/// Smallest and largest number of leaves at each leaf attachment point, before adding the trunk width
+pub(crate) const LEAVES_PER_CLUSTER: (usize, usize) = (5, 15);
+
+/// Chance for each leaf of a fruit tree to be a fruit instead
+const FRUIT_CHANCE: f64 = 0.08;
+const FRUIT_COLORS: [Color; 3] = [Color::Red, Color::DarkYellow, Color::Rgb { r: 255, g: 140, b: 0 }];
+/// The color maple leaves fade to towards the edge of their cluster
+const AUTUMN_TIP: (u8, u8, u8) = (235, 190, 40);
+
 /// Type of leaves. See get_leaf_string()
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum LeafType {
     Pointy,
     Round,
-} 
+    /// Compact clusters of cherry blossoms
+    Blossom,
+    /// Tufts of pine needles on top of the branches
+    Needles,
+    /// Broad maple foliage, turning from red at the heart of a cluster to yellow at its edge
+    Maple,
+    /// Leaves with some fruit among them
+    Fruit,
+}
+
+impl std::str::FromStr for LeafType {
+    type Err = String;
 
-#[derive(Clone)]
+    fn from_str(name: &str) -> std::result::Result<Self, String> {
+        crate::parse_name(name)
+    }
+}
+
+impl LeafType {
+    /// Leaf colors of the types which bring their own, the others use the ones from the config
+    pub fn palette(&self) -> Option<&'static [Color]> {
+        match self {
+            LeafType::Pointy | LeafType::Round => None,
+            LeafType::Blossom => Some(&[ROSE, Color::Rgb { r: 255, g: 183, b: 197 }, Color::White]),
+            LeafType::Needles => Some(&[Color::DarkGreen, Color::Green, Color::Rgb { r: 34, g: 110, b: 60 }]),
+            LeafType::Maple => Some(&[Color::Rgb { r: 178, g: 34, b: 34 }, Color::Rgb { r: 204, g: 85, b: 0 }, Color::Rgb { r: 160, g: 30, b: 60 }]),
+            LeafType::Fruit => Some(&[Color::Green, Color::DarkGreen]),
+        }
+    }
+}
+
+/// Classical bonsai styles, mostly shaping how the trunk grows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Style {
+    /// Trunk wiggling upwards along the noise
+    #[default]
+    InformalUpright,
+    /// Straight trunk
+    FormalUpright,
+    /// Trunk leaning to one side
+    Slanting,
+    /// Trunk arching over the rim of the pot and falling below it
+    Cascade,
+    /// Trunk and branches bent by the wind, branches against it stay short
+    Windswept,
+    /// Tall and thin bare trunk with a bit of foliage at the top
+    Literati,
+}
+
+impl std::str::FromStr for Style {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, String> {
+        crate::parse_name(name)
+    }
+}
+
+impl Style {
+    /// Whether the style leans, falls or gets blown to one side
+    pub fn is_one_sided(&self) -> bool {
+        matches!(self, Style::Slanting | Style::Cascade | Style::Windswept)
+    }
+}
+
+
+/// One leaf attachment point: the max number of leaves, the grown leaves and the min/max extents of the cluster
+pub type LeafAttachment = (usize, Vec<Leaf>, (Point<i16>, Point<i16>));
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Leaf {
     pub pos: Point<i16>,
     pub attached_to: Point<i16>,
@@ -69,7 +257,7 @@ impl std::fmt::Debug for Leaf {
 
 
 /// Defines a step the branch has taken. This is used for drawing
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BonsaiStep {
     /// Current position of the step
     pub pos: Point<i16>,
@@ -77,9 +265,14 @@ pub struct BonsaiStep {
     pub diff: (i16, i16),
     /// The branch width at this step
     pub width: usize,
+    /// The glyphs drawn for this step, empty for the starting point of a branch
+    pub symbol: String,
+    /// Where the glyphs were drawn, relative to `pos`
+    pub draw_offset: (i16, i16),
 }
 
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct BonsaiBranch {
     pub steps: Vec<BonsaiStep>,
     pub direction: Direction,
@@ -87,14 +280,18 @@ pub struct BonsaiBranch {
     pub color: Color,
     pub base_leaf_color: Color,
     /// Each element represent one leaf attachment point with the max number of leaves and a vector of leaves
-    pub leaves: Vec<(usize, Vec<Leaf>, (Point<i16>, Point<i16>))>,
+    pub leaves: Vec<LeafAttachment>,
     pub leaftype: LeafType,
     /// How many steps from the tip of the branch backwards there should be leaves
     max_leaf_positions: usize,
+    /// Number of tree steps taken before this branch grew for the first time
+    pub born: usize,
 }
 
+
 impl BonsaiBranch {
     /// Creates a new bonsai branch
+    #[allow(clippy::too_many_arguments)]
     pub fn new(start_pos: Point<i16>, direction: Direction, start_width: usize, shape: BranchShape, color: Color, max_leaf_positions: usize, leaftype: LeafType, base_leaf_color: Color) -> BonsaiBranch {
         BonsaiBranch {
             steps: vec![
@@ -102,6 +299,8 @@ impl BonsaiBranch {
                     pos: start_pos,
                     width: start_width,
                     diff: (0, 0),
+                    symbol: String::new(),
+                    draw_offset: (0, 0),
                 },
             ],
             direction,
@@ -111,59 +310,80 @@ impl BonsaiBranch {
             leaves: vec![],
             max_leaf_positions,
             leaftype,
+            born: 0,
         }
     }
 
 
     /// Handles a single step of a branch. Uses the last step and given arguments
-    /// to define the next BonsaiStep's width and position
+    /// to define the next BonsaiStep's width and position. `appearance` is the one of the whole tree.
     /// returns if it was able to grow
-    pub fn step(&mut self, noise: &NoiseConfig, rng: &mut RNG, screen_dimensions: (i16, i16), stdout: &mut Writer) -> bool {
+    pub fn step<R: Renderer + ?Sized>(&mut self, appearance: &TreeAppearance, noise: &NoiseConfig, rng: &mut RNG, region: &Region, renderer: &mut R) -> Result<bool> {
         let last_step = self.steps.last().unwrap();
         if last_step.width < 1 {
             //return false;
-            if self.leaves.len() >= self.max_leaf_positions && {
-                let last = self.leaves.last().unwrap();
-                last.1.len() >= last.0
-            } {
-                return false;
+            // Leaves are grown one attachment point after the other, so once the last one is full
+            // the branch is done. Short branches can have less attachment points than max_leaf_positions
+            if self.leaves.last().is_some_and(|last| last.1.len() >= last.0) {
+                return Ok(false);
             }
             if self.leaves.is_empty() {
                 // Determine the attachment points of the leaves
                 let mut positions: Vec<Point<i16>> = self.steps.iter().rev().map(|step| step.pos).collect();
                 positions.truncate(self.max_leaf_positions);
                 // Fill the leaves vector with relevant data
-                positions.iter().for_each(|pos| {
+                // Smaller trees get smaller and less dense leaf clusters
+                let extent = |value: i16| self.shape.scaled(value, 0);
+                let (min_leaves, max_leaves) = appearance.leaves_per_cluster;
+                let leaf_range = (self.shape.scaled(min_leaves as i16, 1) as usize, self.shape.scaled((max_leaves + self.steps[0].width) as i16, 1) as usize);
+                positions.iter().for_each(|_pos| {
                     // TODO: Adjust added width value
-                    let num_leaves = rng.gen_range(5..=15+self.steps[0].width);
+                    let num_leaves = rng.gen_range(leaf_range.0..=leaf_range.1);
                     let (extents_min, extents_max) = match self.leaftype {
                         LeafType::Pointy => {
                             // Flat shape
                             (
-                                Point::from((-rng.gen_range(3..=6), -rng.gen_range(0..=2))),
-                                Point::from((rng.gen_range(3..=6), rng.gen_range(3..=6)))
+                                Point::from((-rng.gen_range(extent(3)..=extent(6)), -rng.gen_range(0..=extent(2)))),
+                                Point::from((rng.gen_range(extent(3)..=extent(6)), rng.gen_range(extent(3)..=extent(6))))
                             )
                         },
-                        LeafType::Round => {
+                        LeafType::Round | LeafType::Fruit => {
                             // Bit more round shape
                             (
-                                Point::from((-rng.gen_range(3..=6), -rng.gen_range(3..=6))),
-                                Point::from((rng.gen_range(3..=6), rng.gen_range(2..=5)))
+                                Point::from((-rng.gen_range(extent(3)..=extent(6)), -rng.gen_range(extent(3)..=extent(6)))),
+                                Point::from((rng.gen_range(extent(3)..=extent(6)), rng.gen_range(extent(2)..=extent(5))))
                             )
-                        }
+                        },
+                        LeafType::Blossom => {
+                            // Small and dense puffs
+                            (
+                                Point::from((-rng.gen_range(extent(2)..=extent(4)), -rng.gen_range(extent(2)..=extent(3)))),
+                                Point::from((rng.gen_range(extent(2)..=extent(4)), rng.gen_range(extent(1)..=extent(2))))
+                            )
+                        },
+                        LeafType::Needles => {
+                            // Narrow tufts standing on top of the branch
+                            (
+                                Point::from((-rng.gen_range(extent(1)..=extent(3)), -rng.gen_range(extent(2)..=extent(4)))),
+                                Point::from((rng.gen_range(extent(1)..=extent(3)), 0))
+                            )
+                        },
+                        LeafType::Maple => {
+                            // Broad crowns
+                            (
+                                Point::from((-rng.gen_range(extent(4)..=extent(7)), -rng.gen_range(extent(3)..=extent(5)))),
+                                Point::from((rng.gen_range(extent(4)..=extent(7)), rng.gen_range(extent(2)..=extent(4))))
+                            )
+                        },
                     };
                     self.leaves.push((num_leaves, vec![], (extents_min, extents_max)));
                 });
             }
-            self.grow_leaf(rng, stdout);
-            return true;
+            self.grow_leaf(appearance, rng, region, renderer)?;
+            return Ok(true);
         };
 
-        let ratio = match self.direction {
-            Direction::Up =>  1.0 - (last_step.pos.y as f32 / (screen_dimensions.1 - 1) as f32),
-            Direction::Left =>  1.0 - (last_step.pos.x as f32 / (screen_dimensions.0 - 1) as f32),
-            Direction::Right =>  last_step.pos.x as f32 / (screen_dimensions.0 - 1) as f32,
-        };
+        let ratio = self.growth_ratio(appearance, region);
         if self.shape.width_loose_chance > self.shape.min_width_loose_chance {
             self.shape.width_loose_chance *= self.shape.width_loose_ratio;
         } else {
@@ -185,64 +405,196 @@ impl BonsaiBranch {
             chance_to_loose_width = 0.5;
         };
 
+        // Literati trunks stay thin but don't end before they reach the top
+        if appearance.style == Style::Literati && self.direction == Direction::Up && ratio < 0.75 {
+            chance_to_loose_width = match new_width > 2 {
+                true => chance_to_loose_width.max(0.9),
+                false => 0.0,
+            };
+        }
+
         if rng.gen_range(0.0..1.0) < chance_to_loose_width {
             new_width -= 1;
         };
 
-        if self.steps.len() > 10 && self.direction != Direction::Up {
+        if self.steps.len() > self.max_length(appearance) && self.direction != Direction::Up {
             new_width = 0;
         };
 
 
-        let mut new_diff = (0 as i16, 0 as i16);
+        let mut new_diff = (0_i16, 0_i16);
         let noise_val = noise.generate_range(last_step.pos.x.into(), last_step.pos.y.into()).round();
         //println!("{} {}", noise_val, noise_val.round());
         match self.direction {
             Direction::Up => {
-                new_diff.0 += noise_val as i16;
-                new_diff.1 -= 1;
+                new_diff = self.trunk_diff(appearance, noise_val as i16);
             },
             Direction::Left => {
                 new_diff.0 -= noise_val.abs() as i16;
                 new_diff.0 -= new_width as i16;
-                if self.steps.len() > 3 && rng.gen_bool(0.3) {
-                    new_diff.1 -= 1 as i16;
-                };
+                new_diff.1 += self.drift(rng);
             },
             Direction::Right => {
                 new_diff.0 += noise_val.abs() as i16;
                 new_diff.0 += new_width as i16;
-                if self.steps.len() > 3 && rng.gen_bool(0.3) {
-                    new_diff.1 -= 1 as i16;
-                };
+                new_diff.1 += self.drift(rng);
             }
         };
 
         let mut new_pos = last_step.pos + Point::<i16>::from(new_diff);
         // The space it leaves for the input line of the terminal (where you type commands)
-        let margin = 3;
-        new_pos.x = std::cmp::max(new_pos.x, margin);
-        new_pos.y = std::cmp::max(new_pos.y, margin);
+        let margin = self.shape.scaled(3, 1);
+        new_pos.x = std::cmp::max(new_pos.x, region.x + margin);
+        new_pos.y = std::cmp::max(new_pos.y, region.y + margin);
+        // Drooping branches stay on the screen
+        new_pos.y = std::cmp::min(new_pos.y, region.bottom() - 1);
+        // Keep the glyphs inside narrow regions
+        new_pos.x = std::cmp::min(new_pos.x, region.right() - 1 - new_width as i16);
         
-        let new_step = BonsaiStep {
+        let mut new_step = BonsaiStep {
             pos: new_pos,
             width: new_width,
             diff: new_diff,
+            symbol: String::new(),
+            draw_offset: (0, 0),
         };
-
-        let dir_string = self.get_string_for_dir(rng, new_step.diff, new_step.width);
-        let mut draw_pos = new_step.pos;
-        if self.direction != Direction::Up && new_step.width <= 1 {
-            draw_pos.y += 1;
+        self.pick_glyphs(appearance, &mut new_step, rng);
+        // Thin branches on the bottom row are drawn where they are instead of one row below the region
+        if new_step.pos.y + new_step.draw_offset.1 >= region.bottom() {
+            new_step.draw_offset.1 = 0;
         }
-        draw(stdout, (draw_pos.x as u16, draw_pos.y as u16), &dir_string, self.color);
+        self.draw_step(&new_step, renderer)?;
 
         self.steps.push(new_step);
-        true
+        Ok(true)
+    }
+
+
+    /// How far the branch has grown towards the edge of the region it grows to, from 0 to 1.
+    /// The closer to the edge, the faster it gets thinner
+    pub fn growth_ratio(&self, appearance: &TreeAppearance, region: &Region) -> f32 {
+        let last_step = self.steps.last().unwrap();
+        match self.direction {
+            // Cascades grow down again, so their progress is counted in steps
+            Direction::Up if appearance.style == Style::Cascade => self.steps.len() as f32 / self.shape.scaled(24, 6) as f32,
+            Direction::Up =>  1.0 - ((last_step.pos.y - region.y) as f32 / (region.height - 1) as f32),
+            Direction::Left =>  1.0 - ((last_step.pos.x - region.x) as f32 / (region.width - 1) as f32),
+            Direction::Right =>  (last_step.pos.x - region.x) as f32 / (region.width - 1) as f32,
+        }
+    }
+
+
+    /// Movement of the next trunk step, shaped by the style. `wiggle` is the noise at the last step
+    fn trunk_diff(&self, appearance: &TreeAppearance, wiggle: i16) -> (i16, i16) {
+        let steps = self.steps.len();
+        let side = appearance.side;
+        match appearance.style {
+            Style::InformalUpright => (wiggle, -1),
+            Style::FormalUpright => (0, -1),
+            // Half the wiggle, so it doesn't undo the lean
+            Style::Slanting => (side + wiggle / 2, -1),
+            // usize::is_multiple_of needs a newer Rust than the rest of bonsai
+            #[allow(clippy::manual_is_multiple_of)]
+            Style::Windswept => (wiggle + side * i16::from(steps % 3 == 0), -1),
+            Style::Literati => (wiggle, -1),
+            Style::Cascade => {
+                let start = &self.steps[0];
+                let last = self.steps.last().unwrap();
+                // Same as the space between the trunk and the walls of the base
+                let margin = self.shape.scaled(4, 2);
+                // Columns between the trunk and the outside of the base on its side
+                let beyond = match side > 0 {
+                    true => last.pos.x - (start.pos.x + start.width as i16 + margin + 1),
+                    false => (start.pos.x - margin - 2) - (last.pos.x + last.width as i16),
+                };
+                if beyond >= 0 {
+                    // Fall down past the base, wiggling away from it
+                    (side * i16::from(wiggle != 0), 1)
+                } else if start.pos.y - last.pos.y < self.shape.scaled(3, 1) {
+                    // Climb out of the base
+                    (side, -1)
+                } else {
+                    // Arch over the rim
+                    (2 * side, 0)
+                }
+            },
+        }
+    }
+
+
+    /// Number of steps after which a side branch stops growing.
+    /// Windswept branches against the wind stay short and the ones with it grow long
+    fn max_length(&self, appearance: &TreeAppearance) -> usize {
+        let length = self.shape.scaled(self.shape.length, 2) as usize;
+        let with_wind = match self.direction {
+            Direction::Left => appearance.side < 0,
+            _ => appearance.side > 0,
+        };
+        match appearance.style {
+            Style::Windswept if with_wind => length * 3 / 2,
+            Style::Windswept => length / 3,
+            Style::Literati => length / 2,
+            _ => length,
+        }
+    }
+
+
+    /// Rows a side branch moves with its next step: up, down (drooping) or none. Young branches grow level
+    fn drift(&self, rng: &mut RNG) -> i16 {
+        if self.steps.len() <= 3 {
+            0
+        } else if rng.gen_bool(self.shape.rise_chance) {
+            -1
+        } else if self.shape.droop_chance > 0.0 && rng.gen_bool(self.shape.droop_chance) {
+            1
+        } else {
+            0
+        }
+    }
+
+
+    /// Number of things (steps and leaves) the branch has drawn while growing, one per tree step
+    pub fn drawn_items(&self) -> usize {
+        // The first step is only the starting point and never drawn
+        self.steps.len().saturating_sub(1) + self.leaves.iter().map(|(_, leaves, _)| leaves.len()).sum::<usize>()
     }
 
 
-    fn grow_leaf(&mut self, rng: &mut RNG, stdout: &mut Writer) {
+    /// Draws the `idx`th thing the branch has drawn while growing, see `drawn_items`
+    pub fn draw_item<R: Renderer + ?Sized>(&self, idx: usize, renderer: &mut R) -> Result<()> {
+        if let Some(step) = self.steps.get(idx + 1) {
+            return self.draw_step(step, renderer);
+        }
+        let leaf = self.leaves.iter()
+            .flat_map(|(_, leaves, _)| leaves)
+            .nth(idx + 1 - self.steps.len());
+        match leaf {
+            Some(leaf) => renderer.draw((leaf.pos.x as u16, leaf.pos.y as u16), &leaf.symbol, leaf.color),
+            None => Ok(()),
+        }
+    }
+
+
+
+    /// Decides which glyphs represent a step and where they are drawn
+    fn pick_glyphs(&self, appearance: &TreeAppearance, step: &mut BonsaiStep, rng: &mut RNG) {
+        step.symbol = self.get_string_for_dir(&appearance.trunk_glyphs, rng, step.diff, step.width);
+        // Thin side branches sit one row lower so they connect to the step they grew from
+        step.draw_offset = match self.direction != Direction::Up && step.width <= 1 {
+            true => (0, 1),
+            false => (0, 0),
+        };
+    }
+
+
+    /// Draws the stored glyphs of a step
+    fn draw_step<R: Renderer + ?Sized>(&self, step: &BonsaiStep, renderer: &mut R) -> Result<()> {
+        let draw_pos = step.pos + Point::from(step.draw_offset);
+        renderer.draw((draw_pos.x as u16, draw_pos.y as u16), &step.symbol, self.color)
+    }
+
+
+    fn grow_leaf<R: Renderer + ?Sized>(&mut self, appearance: &TreeAppearance, rng: &mut RNG, region: &Region, renderer: &mut R) -> Result<()> {
         let num_steps = self.steps.len() - 1;
         let attachment_point = {
             let mut pt = None;
@@ -256,24 +608,49 @@ impl BonsaiBranch {
         };
         
         if let Some(point) = attachment_point {
-            let symbol = Self::get_leaf_string(self.leaftype, rng);
-            let color = match self.base_leaf_color {
-                Color::Rgb { r, g, b } => {
-                    Color::Rgb {
-                        r: r.saturating_sub(rng.gen_range(0..20)),
-                        g: g.saturating_sub(rng.gen_range(0..20)),
-                        b: b.saturating_sub(rng.gen_range(0..20)),
-                    }
-                },
-                _ => self.base_leaf_color,
+            let is_fruit = self.leaftype == LeafType::Fruit && rng.gen_bool(FRUIT_CHANCE);
+            let symbol = match is_fruit {
+                true => Self::get_fruit_string(&appearance.fruit_glyphs, rng),
+                false => Self::get_leaf_string(self.leaftype, &appearance.leaf_glyphs, rng),
+            };
+            // Every leaf gets a bit darker or lighter than the others
+            let shade = match self.base_leaf_color {
+                Color::Rgb { .. } => (rng.gen_range(0..20), rng.gen_range(0..20), rng.gen_range(0..20)),
+                _ => (0, 0, 0),
             };
             let min = point.2.0;
             let max = point.2.1;
             let rand_x = rng.gen_range(min.x..=max.x);
             let rand_y = rng.gen_range(min.y..=max.y);
+            let base_color = match self.leaftype {
+                _ if is_fruit => *FRUIT_COLORS.choose(rng).unwrap(),
+                LeafType::Maple => {
+                    // How far out in the cluster the leaf is, from 0 at the attachment point to 1 at its edge
+                    let reach = |offset: i16, min: i16, max: i16| match offset < 0 {
+                        true if min < 0 => offset as f32 / min as f32,
+                        false if max > 0 => offset as f32 / max as f32,
+                        _ => 0.0,
+                    };
+                    let ratio = reach(rand_x, min.x, max.x).max(reach(rand_y, min.y, max.y));
+                    // Squared, so only the outer leaves turn really yellow
+                    Self::autumn_gradient(self.base_leaf_color, ratio * ratio)
+                },
+                _ => self.base_leaf_color,
+            };
+            let color = match base_color {
+                Color::Rgb { r, g, b } if !is_fruit => Color::Rgb {
+                    r: r.saturating_sub(shade.0),
+                    g: g.saturating_sub(shade.1),
+                    b: b.saturating_sub(shade.2),
+                },
+                _ => base_color,
+            };
             let mut new_pos = point.0 + Point::from((rand_x, rand_y));
-            new_pos.x = std::cmp::max(3, new_pos.x);
-            new_pos.y = std::cmp::max(3, new_pos.y);
+            let margin = self.shape.scaled(3, 1);
+            new_pos.x = std::cmp::max(region.x + margin, new_pos.x);
+            new_pos.y = std::cmp::max(region.y + margin, new_pos.y);
+            new_pos.x = std::cmp::min(new_pos.x, region.right() - text_width(&symbol) as i16);
+            new_pos.y = std::cmp::min(new_pos.y, region.bottom() - 1);
             
             //println!("Attach: {}, pos: {}", point.0, new_pos);
             
@@ -283,41 +660,78 @@ impl BonsaiBranch {
                 symbol: symbol.clone(),
                 color,
             });
-            draw(stdout, (new_pos.x as u16, new_pos.y as u16), symbol.as_str(), color)
+            renderer.draw((new_pos.x as u16, new_pos.y as u16), symbol.as_str(), color)?;
         }
+        Ok(())
     }
 
     /// Depending on the leaf type, returns one of the leaf appearance characters
-    fn get_leaf_string(leaftype: LeafType, rng: &mut RNG) -> String {
+    fn get_leaf_string(leaftype: LeafType, glyphs: &[String], rng: &mut RNG) -> String {
+        if let Some(glyph) = glyphs.choose(rng) {
+            return glyph.clone();
+        }
         match leaftype {
             LeafType::Pointy => POINTY_LEAVES.choose(rng).unwrap().to_string(),
             LeafType::Round => ROUND_LEAVES.choose(rng).unwrap().to_string(),
+            LeafType::Blossom => BLOSSOM_LEAVES.choose(rng).unwrap().to_string(),
+            LeafType::Needles => NEEDLE_LEAVES.choose(rng).unwrap().to_string(),
+            LeafType::Maple => MAPLE_LEAVES.choose(rng).unwrap().to_string(),
+            LeafType::Fruit => FRUIT_LEAVES.choose(rng).unwrap().to_string(),
+        }
+    }
+
+    /// Returns one of the fruit characters
+    fn get_fruit_string(glyphs: &[String], rng: &mut RNG) -> String {
+        match glyphs.choose(rng) {
+            Some(glyph) => glyph.clone(),
+            None => FRUITS.choose(rng).unwrap().to_string(),
+        }
+    }
+
+    /// Blends the leaf color towards autumn yellow, `ratio` 0 keeps the leaf color and 1 is fully yellow.
+    /// Colors which aren't RGB can't be blended and stay as they are
+    fn autumn_gradient(color: Color, ratio: f32) -> Color {
+        let Color::Rgb { r, g, b } = color else {
+            return color;
+        };
+        let blend = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * ratio.clamp(0.0, 1.0)).round() as u8;
+        Color::Rgb {
+            r: blend(r, AUTUMN_TIP.0),
+            g: blend(g, AUTUMN_TIP.1),
+            b: blend(b, AUTUMN_TIP.2),
         }
     }
 
     /// Depending on the direction, returns a string the looks like the direction
-    pub fn get_string_for_dir(&mut self, rng: &mut RNG, mut dir: (i16, i16), width: usize) -> String {
-        dir.0 = std::cmp::max(std::cmp::min(1, dir.0), -1);
-        dir.1 = std::cmp::max(std::cmp::min(1, dir.1), -1);
+    pub fn get_string_for_dir(&self, glyphs: &TrunkGlyphs, rng: &mut RNG, mut dir: (i16, i16), width: usize) -> String {
+        dir.0 = dir.0.clamp(-1, 1);
+        dir.1 = dir.1.clamp(-1, 1);
     
         let set: Vec<char> = match dir {
-            // Straight up
-            (0, -1) | (0, 0) => vec!['/', '|', '\\'],
+            // Straight up, or straight down for drooping branches
+            (0, -1) | (0, 0) | (0, 1) => glyphs.up.chars().collect(),
             // Up left or down right
-            (-1, -1) | (1, 1) => vec!['\\', '~',],
+            (-1, -1) | (1, 1) => glyphs.up_left.chars().collect(),
             // Up right or down left
-            (1, -1) | (-1, 1) => vec!['/', '~', '\\'],
+            (1, -1) | (-1, 1) => glyphs.up_right.chars().collect(),
             // left
-            (-1, 0) => vec!['\\', '~', '-', '_', '='],
+            (-1, 0) => glyphs.left.chars().collect(),
             // right
-            (1, 0) => vec!['/', '~', '-', '_', '='],
+            (1, 0) => glyphs.right.chars().collect(),
             // Stupid dir
             _ => vec!['?']
         };
-        let mut result = String::new();
-        result.push(set[0]);
+        let first = *set.first().unwrap_or(&'?');
+        let mut result = String::from(first);
+        // The step covers `width + 1` cells. Wide characters which don't fit anymore are left out,
+        // but still picked so the rest of the tree grows the same with any glyphs
+        let mut cells = char_width(first);
         for _i in 0..width {
-            result.push(*set.choose(rng).unwrap_or(&'?'));
+            let symbol = *set.choose(rng).unwrap_or(&'?');
+            if cells + char_width(symbol) <= width + 1 {
+                cells += char_width(symbol);
+                result.push(symbol);
+            }
         };
         result
     }
@@ -0,0 +1,228 @@
+use crossterm::style::{Color, Stylize};
+
+use crate::{forest::Forest, render::{char_width, Renderer}, BonsaiTree, Result};
+
+
+/// A single character cell of a canvas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Color,
+}
+
+impl Cell {
+    /// Symbol of the second cell covered by a wide character, which is drawn by the first one
+    pub const CONTINUATION: char = '\0';
+
+    /// Whether the cell is covered by the wide character in the cell left of it
+    pub fn is_continuation(&self) -> bool {
+        self.symbol == Cell::CONTINUATION
+    }
+}
+
+
+
+/// Records everything a tree draws into a 2D grid of cells instead of a terminal.
+/// Later draws overwrite earlier ones, just like on a terminal.
+#[derive(Debug, Clone)]
+pub struct Canvas {
+    width: u16,
+    height: u16,
+    /// Row-major cells, `None` where nothing has been drawn yet
+    cells: Vec<Option<Cell>>,
+}
+
+impl Canvas {
+    /// Creates an empty canvas with the given size in cells
+    pub fn new(width: u16, height: u16) -> Canvas {
+        Canvas {
+            width,
+            height,
+            cells: vec![None; width as usize * height as usize],
+        }
+    }
+
+    /// Creates an empty canvas large enough for the region the tree grows in
+    pub fn for_tree(tree: &BonsaiTree) -> Canvas {
+        Canvas::new(tree.region.right().max(0) as u16, tree.region.bottom().max(0) as u16)
+    }
+
+    /// Creates an empty canvas large enough for all trees of the forest
+    pub fn for_forest(forest: &Forest) -> Canvas {
+        let region = forest.region();
+        Canvas::new(region.right().max(0) as u16, region.bottom().max(0) as u16)
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the cell at the given position, `None` if it is empty or out of bounds
+    pub fn get(&self, x: u16, y: u16) -> Option<Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Iterates over all rows from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[Option<Cell>]> {
+        self.cells.chunks(self.width.max(1) as usize)
+    }
+
+    /// Empties every cell
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = None);
+    }
+
+    /// Returns the smallest rectangle `(min_x, min_y, max_x, max_y)` containing every
+    /// visible (non-space) cell, `None` if nothing visible was drawn
+    pub fn bounds(&self) -> Option<(u16, u16, u16, u16)> {
+        let mut bounds: Option<(u16, u16, u16, u16)> = None;
+        for (y, row) in self.rows().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell.is_some_and(|c| c.symbol != ' ') {
+                    let (x, y) = (x as u16, y as u16);
+                    bounds = Some(match bounds {
+                        Some((min_x, min_y, max_x, max_y)) => (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)),
+                        None => (x, y, x, y),
+                    });
+                }
+            }
+        }
+        bounds
+    }
+
+    /// Returns the picture as plain text.
+    /// Empty rows above and below the drawing and trailing spaces are left out
+    pub fn to_plain_string(&self) -> String {
+        self.to_string_with(|text, _| text.to_string())
+    }
+
+    /// Returns the picture as text colored with ANSI escape sequences.
+    /// Empty rows above and below the drawing and trailing spaces are left out
+    pub fn to_ansi_string(&self) -> String {
+        self.to_string_with(|text, color| text.with(color).to_string())
+    }
+
+    /// Joins the used rows, passing every run of equally colored characters through `style`
+    fn to_string_with<F: Fn(&str, Color) -> String>(&self, style: F) -> String {
+        let is_empty = |row: &&[Option<Cell>]| row.iter().all(|cell| !cell.is_some_and(|c| c.symbol != ' '));
+        let rows: Vec<&[Option<Cell>]> = self.rows().collect();
+        let first = rows.iter().position(|row| !is_empty(row)).unwrap_or(rows.len());
+        let last = rows.iter().rposition(|row| !is_empty(row)).map_or(first, |idx| idx + 1);
+
+        let mut result = String::new();
+        for row in &rows[first..last] {
+            let used = row.iter().rposition(|cell| cell.is_some_and(|c| c.symbol != ' ')).map_or(0, |idx| idx + 1);
+            let mut run = String::new();
+            let mut run_color = None;
+            for cell in row[..used].iter().filter(|cell| !cell.is_some_and(|c| c.is_continuation())) {
+                let (symbol, color) = cell.map_or((' ', None), |c| (c.symbol, Some(c.color)));
+                // Spaces don't need a color, so they can join any run
+                if symbol != ' ' && color != run_color {
+                    if !run.is_empty() {
+                        result.push_str(&Self::style_run(&style, &run, run_color));
+                        run.clear();
+                    }
+                    run_color = color;
+                }
+                run.push(symbol);
+            }
+            result.push_str(&Self::style_run(&style, &run, run_color));
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Puts `cell` at the position. Wide characters it partly covers are replaced with spaces,
+    /// like a terminal does
+    fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        let idx = y as usize * self.width as usize + x as usize;
+        let old = self.cells[idx];
+        if old.is_some_and(|old| old.is_continuation()) && !cell.is_continuation() && x > 0 {
+            if let Some(lead) = &mut self.cells[idx - 1] {
+                lead.symbol = ' ';
+            }
+        }
+        if old.is_some_and(|old| char_width(old.symbol) == 2) && x + 1 < self.width {
+            if let Some(tail) = &mut self.cells[idx + 1] {
+                tail.symbol = ' ';
+            }
+        }
+        self.cells[idx] = Some(cell);
+    }
+
+    fn style_run<F: Fn(&str, Color) -> String>(style: &F, run: &str, color: Option<Color>) -> String {
+        match color {
+            Some(color) => style(run, color),
+            None => run.to_string(),
+        }
+    }
+}
+
+impl Renderer for Canvas {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        if pos.1 >= self.height {
+            return Ok(());
+        }
+        let mut x = pos.0;
+        for symbol in what.chars() {
+            let width = char_width(symbol) as u16;
+            // Wide characters are left out rather than cut in half at the edge
+            if x + width > self.width {
+                break;
+            }
+            self.set(x, pos.1, Cell { symbol, color });
+            if width == 2 {
+                self.set(x + 1, pos.1, Cell { symbol: Cell::CONTINUATION, color });
+            }
+            x += width;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn later_draws_overwrite_earlier_ones() {
+        let mut canvas = Canvas::new(6, 2);
+        canvas.draw((0, 0), "abc", Color::Red).unwrap();
+        canvas.draw((1, 0), "X", Color::Green).unwrap();
+        assert_eq!(canvas.get(0, 0), Some(Cell { symbol: 'a', color: Color::Red }));
+        assert_eq!(canvas.get(1, 0), Some(Cell { symbol: 'X', color: Color::Green }));
+        assert_eq!(canvas.get(2, 0), Some(Cell { symbol: 'c', color: Color::Red }));
+        assert_eq!(canvas.get(3, 0), None);
+        assert_eq!(canvas.to_plain_string(), "aXc\n");
+    }
+
+    #[test]
+    fn draws_outside_are_cut_off() {
+        let mut canvas = Canvas::new(4, 1);
+        canvas.draw((2, 0), "abc", Color::Red).unwrap();
+        canvas.draw((0, 1), "d", Color::Red).unwrap();
+        assert_eq!(canvas.to_plain_string(), "  ab\n");
+    }
+
+    #[test]
+    fn overwriting_half_a_wide_character_blanks_the_other_half() {
+        let mut canvas = Canvas::new(6, 1);
+        canvas.draw((0, 0), "🌸🌸", Color::Red).unwrap();
+        assert_eq!(canvas.get(1, 0).map(|cell| cell.is_continuation()), Some(true));
+        canvas.draw((1, 0), "x", Color::Green).unwrap();
+        canvas.draw((2, 0), "y", Color::Green).unwrap();
+        assert_eq!(canvas.to_plain_string(), " xy\n");
+    }
+}
@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use crate::{appearance::BaseType, branch::{Branching, BranchShape, LeafType, Style, TrunkGlyphs, BLOSSOM_LEAVES, FRUITS, FRUIT_LEAVES, LEAVES_PER_CLUSTER, MAPLE_LEAVES, NEEDLE_LEAVES, POINTY_LEAVES, ROUND_LEAVES}, Error, Result, BROWN, ROSE};
+
+
+/// Colors of the base (plant pot)
+const BASE_COLORS: [Color; 3] = [Color::DarkGrey, Color::Grey, Color::Rgb { r: 176, g: 94, b: 59 }];
+/// Upper bounds for the sizes of trees, so positions computed from them still fit into an i16
+const MAX_TRUNK_WIDTH: usize = 100;
+const MAX_LEAVES_PER_CLUSTER: usize = 1000;
+/// Largest `noise_frequency`, the noise overflows with positions scaled much further
+const MAX_NOISE_FREQUENCY: f32 = 1000.0;
+
+
+/// Defaults for everything that gets picked at random while growing a tree.
+/// Can be read from a TOML file, missing keys keep the built-in values
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Color of the trunk and the branches
+    #[serde(deserialize_with = "deserialize_color")]
+    pub trunk_color: Color,
+    /// Leaf types, one of them is picked for each tree
+    pub leaf_types: Vec<LeafType>,
+    /// Leaf colors, one of them is picked for each tree. Only for pointy and round leaves, the others bring their own
+    #[serde(deserialize_with = "deserialize_colors")]
+    pub leaf_colors: Vec<Color>,
+    /// Chance for a tree with (almost) black leaves instead of one of `leaf_colors`
+    pub dark_leaf_chance: f64,
+    /// Colors of the base, one of them is picked for each tree
+    #[serde(deserialize_with = "deserialize_colors")]
+    pub base_colors: Vec<Color>,
+    /// Smallest and largest trunk width, if none is given
+    pub trunk_width: (usize, usize),
+    /// Smallest and largest number of steps from the tip of each branch backwards which get leaves
+    pub leaf_count: (usize, usize),
+    /// Smallest and largest number of leaves at each of these steps. The trunk width is added to the largest
+    pub leaves_per_cluster: (usize, usize),
+    /// Frequency of the noise which makes the trunk wiggle
+    pub noise_frequency: f32,
+    pub leaves: LeafSets,
+    /// The strings fruit trees grow among their leaves
+    pub fruits: Vec<String>,
+    pub trunk_glyphs: TrunkGlyphs,
+    pub trunk_shape: BranchShape,
+    pub branch_shape: BranchShape,
+    pub branching: Branching,
+    /// Bonsai style, shaping how the trunk grows
+    pub style: Style,
+    pub pin: Pins,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            trunk_color: BROWN,
+            leaf_types: vec![LeafType::Pointy, LeafType::Round],
+            leaf_colors: vec![Color::Green, Color::Red, Color::Yellow, ROSE],
+            dark_leaf_chance: 0.05,
+            base_colors: BASE_COLORS.to_vec(),
+            trunk_width: (5, 14),
+            leaf_count: (2, 4),
+            leaves_per_cluster: LEAVES_PER_CLUSTER,
+            noise_frequency: 4.0,
+            leaves: LeafSets::default(),
+            fruits: to_strings(&FRUITS),
+            trunk_glyphs: TrunkGlyphs::default(),
+            trunk_shape: BranchShape::default_trunk(),
+            branch_shape: BranchShape::default_branch(),
+            branching: Branching::default(),
+            style: Style::default(),
+            pin: Pins::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads a config file
+    pub fn load(path: &Path) -> Result<Config> {
+        let contents = std::fs::read_to_string(path).map_err(|err| Error::Config(path.to_path_buf(), err.to_string()))?;
+        let config: Config = toml::from_str(&contents).map_err(|err| Error::Config(path.to_path_buf(), err.to_string()))?;
+        config.check().map_err(|message| Error::Config(path.to_path_buf(), message))?;
+        Ok(config)
+    }
+
+    /// Reads the config file at `default_path`, or returns the built-in values if there is none
+    pub fn load_default() -> Result<Config> {
+        match Config::default_path() {
+            Some(path) if path.exists() => Config::load(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// `$XDG_CONFIG_HOME/bonsai/config.toml`, falling back to `~/.config/bonsai/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("bonsai").join("config.toml"))
+    }
+
+    /// Checks everything the growth relies on, like non-empty lists and ranges.
+    /// Configs built in code should be checked before growing trees with them, `BonsaiTree::in_region` does so
+    pub fn validate(&self) -> Result<()> {
+        self.check().map_err(Error::InvalidConfig)
+    }
+
+    /// Like `validate`, but only returns what is wrong
+    fn check(&self) -> std::result::Result<(), String> {
+        let range = |name: &str, (min, max): (usize, usize), lowest: usize| match min >= lowest && min <= max {
+            true => Ok(()),
+            false => Err(format!("`{}` needs a minimum of at least {} which isn't larger than the maximum", name, lowest)),
+        };
+        range("trunk_width", self.trunk_width, 1)?;
+        range("leaf_count", self.leaf_count, 1)?;
+        range("leaves_per_cluster", self.leaves_per_cluster, 1)?;
+        if self.trunk_width.1 > MAX_TRUNK_WIDTH || self.leaves_per_cluster.1 > MAX_LEAVES_PER_CLUSTER {
+            return Err(format!(
+                "`trunk_width` can't be more than {} and `leaves_per_cluster` not more than {}",
+                MAX_TRUNK_WIDTH, MAX_LEAVES_PER_CLUSTER
+            ));
+        }
+        if !(self.noise_frequency > 0.0 && self.noise_frequency <= MAX_NOISE_FREQUENCY) {
+            return Err(format!("`noise_frequency` has to be larger than 0 and at most {}", MAX_NOISE_FREQUENCY));
+        }
+        if self.leaf_types.is_empty() {
+            return Err("`leaf_types` needs at least one leaf type".to_string());
+        }
+        if self.leaf_colors.is_empty() || self.base_colors.is_empty() {
+            return Err("`leaf_colors` and `base_colors` need at least one color".to_string());
+        }
+        let sets = self.leaves.all().into_iter().chain([&self.fruits]);
+        if sets.clone().any(|set| set.is_empty()) {
+            return Err("every leaf set and `fruits` need at least one leaf".to_string());
+        }
+        if sets.clone().flatten().any(String::is_empty) {
+            return Err("leaves can't be empty strings".to_string());
+        }
+        if !self.trunk_glyphs.is_complete() {
+            return Err("every direction in `trunk_glyphs` needs at least one character".to_string());
+        }
+        // They would end up as escape codes in the terminal or get lost on the canvas
+        if sets.flatten().chain(self.trunk_glyphs.sets()).any(|glyphs| glyphs.chars().any(char::is_control)) {
+            return Err("leaves, `fruits` and `trunk_glyphs` can't contain control characters".to_string());
+        }
+        if self.pin.leaf_count == Some(0) {
+            return Err("the pinned `leaf_count` has to be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.dark_leaf_chance) {
+            return Err("`dark_leaf_chance` has to be between 0 and 1".to_string());
+        }
+        for shape in [&self.trunk_shape, &self.branch_shape] {
+            if !(0.0..=1.0).contains(&shape.rise_chance) || !(0.0..=1.0).contains(&shape.droop_chance) {
+                return Err("`rise_chance` and `droop_chance` have to be between 0 and 1".to_string());
+            }
+            let chances = [shape.width_loose_chance, shape.min_width_loose_chance, shape.width_loose_ratio];
+            if !chances.iter().all(|chance| (0.0..=1.0).contains(chance)) {
+                return Err("`width_loose_chance`, `min_width_loose_chance` and `width_loose_ratio` have to be between 0 and 1".to_string());
+            }
+            if shape.length < 1 {
+                return Err("branch `length` has to be at least 1".to_string());
+            }
+        }
+        let branching = &self.branching;
+        if !(0.0..=1.0).contains(&branching.start) || !(-1.0..=1.0).contains(&branching.bias) {
+            return Err("`branching.start` has to be between 0 and 1 and `branching.bias` between -1 and 1".to_string());
+        }
+        if !(branching.spacing >= 0.0 && branching.frequency >= 0.0) || branching.spacing.is_infinite() || branching.frequency.is_infinite() {
+            return Err("`branching.spacing` and `branching.frequency` have to be finite and can't be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+
+/// The strings leaves are picked from, for each type of leaf. Wide characters like emoji take two cells
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LeafSets {
+    pub pointy: Vec<String>,
+    pub round: Vec<String>,
+    pub blossom: Vec<String>,
+    pub needles: Vec<String>,
+    pub maple: Vec<String>,
+    /// The leaves of fruit trees, the fruit themselves are `Config::fruits`
+    pub fruit: Vec<String>,
+}
+
+impl Default for LeafSets {
+    fn default() -> Self {
+        LeafSets {
+            pointy: to_strings(&POINTY_LEAVES),
+            round: to_strings(&ROUND_LEAVES),
+            blossom: to_strings(&BLOSSOM_LEAVES),
+            needles: to_strings(&NEEDLE_LEAVES),
+            maple: to_strings(&MAPLE_LEAVES),
+            fruit: to_strings(&FRUIT_LEAVES),
+        }
+    }
+}
+
+impl LeafSets {
+    /// The leaves of a leaf type
+    pub fn get(&self, leaf_type: LeafType) -> &[String] {
+        match leaf_type {
+            LeafType::Pointy => &self.pointy,
+            LeafType::Round => &self.round,
+            LeafType::Blossom => &self.blossom,
+            LeafType::Needles => &self.needles,
+            LeafType::Maple => &self.maple,
+            LeafType::Fruit => &self.fruit,
+        }
+    }
+
+    /// Replaces the leaves of every leaf type
+    pub fn set_all(&mut self, leaves: &[String]) {
+        for set in [&mut self.pointy, &mut self.round, &mut self.blossom, &mut self.needles, &mut self.maple, &mut self.fruit] {
+            *set = leaves.to_vec();
+        }
+    }
+
+    fn all(&self) -> [&Vec<String>; 6] {
+        [&self.pointy, &self.round, &self.blossom, &self.needles, &self.maple, &self.fruit]
+    }
+}
+
+
+fn to_strings(leaves: &[&str]) -> Vec<String> {
+    leaves.iter().map(|leaf| leaf.to_string()).collect()
+}
+
+
+/// Appearance choices which are always the same instead of random
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pins {
+    pub leaf_type: Option<LeafType>,
+    #[serde(deserialize_with = "deserialize_optional_color")]
+    pub leaf_color: Option<Color>,
+    /// Number of steps from the tip of each branch backwards which get leaves
+    pub leaf_count: Option<usize>,
+    pub base: Option<BaseType>,
+}
+
+
+/// Parses a color name (`dark_green`, `grey`...) or a hex color (`#rrggbb`)
+pub fn parse_color(value: &str) -> std::result::Result<Color, String> {
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |idx: usize| hex.get(idx..idx + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+        return match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+            _ => Err(format!("invalid hex color `{}`, expected `#rrggbb`", value)),
+        };
+    }
+    value.parse().map_err(|_| format!("unknown color `{}`", value))
+}
+
+
+fn deserialize_color<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Color, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    parse_color(&value).map_err(serde::de::Error::custom)
+}
+
+
+fn deserialize_colors<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Vec<Color>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|value| parse_color(value).map_err(serde::de::Error::custom))
+        .collect()
+}
+
+
+fn deserialize_optional_color<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<Color>, D::Error> {
+    deserialize_color(deserializer).map(Some)
+}
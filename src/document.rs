@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{appearance::TreeAppearance, point::Point, region::Region, branch::BonsaiBranch, draw_tree, render::Renderer, BonsaiTree, Error, Result, MAX_GROWTH_STEPS, MIN_SCALE, MIN_SIZE};
+
+
+/// Largest screen (columns and rows) a document may describe, keeps the canvas for drawing it small
+const MAX_SIZE: i16 = 1000;
+
+
+/// A grown tree with everything needed to draw it again without regrowing it.
+/// Can be stored as JSON or RON
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TreeDocument {
+    /// Version of bonsai which grew the tree
+    pub version: String,
+    pub seed: u64,
+    /// Size of the screen the tree was grown on
+    pub width: i16,
+    pub height: i16,
+    /// Part of the screen the tree grew in
+    pub region: Region,
+    pub appearance: TreeAppearance,
+    /// All branches, the first one is the trunk
+    pub branches: Vec<BonsaiBranch>,
+}
+
+impl TreeDocument {
+    /// Takes a snapshot of the tree in its current state
+    pub fn from_tree(tree: &BonsaiTree) -> TreeDocument {
+        TreeDocument {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: tree.seed(),
+            width: tree.region.right(),
+            height: tree.region.bottom(),
+            region: tree.region,
+            appearance: tree.appearance.clone(),
+            branches: tree.branches().to_vec(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<TreeDocument> {
+        serde_json::from_str::<TreeDocument>(json)?.validate()
+    }
+
+    pub fn to_ron(&self) -> Result<String> {
+        Ok(ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?)
+    }
+
+    pub fn from_ron(ron: &str) -> Result<TreeDocument> {
+        ron::from_str::<TreeDocument>(ron)?.validate()
+    }
+
+    /// Makes sure the document describes a tree that can be drawn, documents might have been edited by hand
+    pub fn validate(self) -> Result<TreeDocument> {
+        let invalid = |message: String| Err(Error::Document(message));
+        if !(MIN_SIZE.0 as i16..=MAX_SIZE).contains(&self.width) || !(MIN_SIZE.1 as i16..=MAX_SIZE).contains(&self.height) {
+            return invalid(format!(
+                "screen size {}x{} is not between {}x{} and {}x{}",
+                self.width, self.height, MIN_SIZE.0, MIN_SIZE.1, MAX_SIZE, MAX_SIZE
+            ));
+        }
+        let region = self.region;
+        if region.x < 0 || region.y < 0 || region.width < MIN_SIZE.0 as i16 || region.height < MIN_SIZE.1 as i16
+            || region.right() > self.width || region.bottom() > self.height {
+            return invalid(format!("region {:?} doesn't fit on the {}x{} screen", region, self.width, self.height));
+        }
+        let appearance = &self.appearance;
+        if !(MIN_SCALE..=1.0).contains(&appearance.scale) {
+            return invalid(format!("scale {} is not between {} and 1", appearance.scale, MIN_SCALE));
+        }
+        if appearance.trunk_width > self.width as usize {
+            return invalid(format!("trunk width {} is wider than the screen", appearance.trunk_width));
+        }
+        let has_control_chars = appearance.leaf_glyphs.iter()
+            .chain(&appearance.fruit_glyphs)
+            .chain(appearance.trunk_glyphs.sets())
+            .any(|glyphs| glyphs.chars().any(char::is_control));
+        if has_control_chars || !appearance.trunk_glyphs.is_complete() {
+            return invalid("the glyphs of the tree are empty or contain control characters".to_string());
+        }
+        if self.branches.is_empty() {
+            return invalid("the tree has no branches".to_string());
+        }
+        for (idx, branch) in self.branches.iter().enumerate() {
+            if branch.steps.is_empty() {
+                return invalid(format!("branch {} has no steps", idx));
+            }
+            if branch.born > MAX_GROWTH_STEPS {
+                return invalid(format!("branch {} is born after more than {} steps", idx, MAX_GROWTH_STEPS));
+            }
+            if let Some(step) = branch.steps.iter().find(|step| step.draw_offset.0.abs() > 1 || step.draw_offset.1.abs() > 1) {
+                return invalid(format!("branch {} has a step drawn {:?} away from its position", idx, step.draw_offset));
+            }
+            let steps = branch.steps.iter()
+                .map(|step| Point::from((step.pos.x.saturating_add(step.draw_offset.0), step.pos.y.saturating_add(step.draw_offset.1))));
+            let leaves = branch.leaves.iter().flat_map(|(_, leaves, _)| leaves).map(|leaf| leaf.pos);
+            let on_screen = |pos: &Point<i16>| (0..=MAX_SIZE).contains(&pos.x) && (0..=MAX_SIZE).contains(&pos.y);
+            if let Some(pos) = steps.chain(leaves).find(|pos| !on_screen(pos)) {
+                return invalid(format!("branch {} draws at ({}, {}), outside the screen", idx, pos.x, pos.y));
+            }
+            let mut symbols = branch.steps.iter().map(|step| &step.symbol)
+                .chain(branch.leaves.iter().flat_map(|(_, leaves, _)| leaves).map(|leaf| &leaf.symbol));
+            if symbols.any(|symbol| symbol.chars().any(char::is_control)) {
+                return invalid(format!("branch {} draws control characters", idx));
+            }
+        }
+        Ok(self)
+    }
+
+    /// Draws the stored tree including its base and caption, just like it looked when it was exported
+    pub fn render<R: Renderer + ?Sized>(&self, renderer: &mut R) -> Result<()> {
+        draw_tree(&self.appearance, self.seed, &self.region, &self.branches, renderer)
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+
+/// Everything that can go wrong while growing, drawing or storing a tree
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing the terminal or a file failed
+    Io(std::io::Error),
+    /// The tree should be grown on a terminal, but stdout isn't one
+    NotATerminal,
+    /// The screen can't fit even the smallest tree
+    TerminalTooSmall {
+        width: u16,
+        height: u16,
+        min_width: u16,
+        min_height: u16,
+    },
+    /// A JSON document couldn't be written or read
+    Json(serde_json::Error),
+    /// A RON document couldn't be written
+    Ron(ron::Error),
+    /// A RON document couldn't be read
+    RonSyntax(ron::error::SpannedError),
+    Png(png::EncodingError),
+    Gif(gif::EncodingError),
+    /// An image would have these dimensions (in pixels), which its format can't store
+    ImageSize(u32, u32),
+    /// The config file at the path couldn't be read or is invalid
+    Config(std::path::PathBuf, String),
+    /// A config built in code is invalid
+    InvalidConfig(String),
+    /// A document was read fine but describes a tree which can't be drawn
+    Document(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::NotATerminal => write!(f, "not a terminal, use --print to write the tree to stdout instead"),
+            Error::TerminalTooSmall { width, height, min_width, min_height } => write!(
+                f,
+                "terminal too small ({}x{}), the tree needs at least {}x{}",
+                width, height, min_width, min_height
+            ),
+            Error::Json(err) => write!(f, "invalid JSON document: {}", err),
+            Error::Ron(err) => write!(f, "invalid RON document: {}", err),
+            Error::RonSyntax(err) => write!(f, "invalid RON document: {}", err),
+            Error::Png(err) => write!(f, "could not encode PNG: {}", err),
+            Error::Gif(err) => write!(f, "could not encode GIF: {}", err),
+            Error::ImageSize(width, height) => write!(f, "can't write an image of {}x{} pixels", width, height),
+            Error::Config(path, message) => write!(f, "config file {}: {}", path.display(), message),
+            Error::InvalidConfig(message) => write!(f, "invalid config: {}", message),
+            Error::Document(message) => write!(f, "invalid tree document: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Ron(err) => Some(err),
+            Error::RonSyntax(err) => Some(err),
+            Error::Png(err) => Some(err),
+            Error::Gif(err) => Some(err),
+            Error::NotATerminal | Error::TerminalTooSmall { .. } | Error::ImageSize(..) | Error::Config(..) | Error::InvalidConfig(_) | Error::Document(_) => None,
+        }
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<ron::Error> for Error {
+    fn from(err: ron::Error) -> Self {
+        Error::Ron(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for Error {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Error::RonSyntax(err)
+    }
+}
+
+impl From<png::EncodingError> for Error {
+    fn from(err: png::EncodingError) -> Self {
+        Error::Png(err)
+    }
+}
+
+impl From<gif::EncodingError> for Error {
+    fn from(err: gif::EncodingError) -> Self {
+        Error::Gif(err)
+    }
+}
@@ -0,0 +1,76 @@
+use crossterm::style::Color;
+
+use crate::canvas::Canvas;
+
+pub mod svg;
+pub mod raster;
+pub mod png;
+pub mod asciicast;
+pub mod gif;
+
+
+/// Returns the area `(min_x, min_y, max_x, max_y)` of the canvas to export.
+/// When cropping, that's everything visible plus `padding` empty cells, otherwise the whole canvas
+pub fn export_area(canvas: &Canvas, crop: bool, padding: u16) -> (u16, u16, u16, u16) {
+    let max_x = canvas.width().saturating_sub(1);
+    let max_y = canvas.height().saturating_sub(1);
+    match (crop, canvas.bounds()) {
+        (true, Some((min_x, min_y, used_x, used_y))) => (
+            min_x.saturating_sub(padding),
+            min_y.saturating_sub(padding),
+            used_x.saturating_add(padding).min(max_x),
+            used_y.saturating_add(padding).min(max_y),
+        ),
+        _ => (0, 0, max_x, max_y),
+    }
+}
+
+
+/// Converts a terminal color into RGB values, using the xterm palette for named and ANSI colors
+pub fn color_to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Rgb { r, g, b } => (r, g, b),
+        Color::AnsiValue(value) => ansi_to_rgb(value),
+        Color::Black => ansi_to_rgb(0),
+        Color::DarkRed => ansi_to_rgb(1),
+        Color::DarkGreen => ansi_to_rgb(2),
+        Color::DarkYellow => ansi_to_rgb(3),
+        Color::DarkBlue => ansi_to_rgb(4),
+        Color::DarkMagenta => ansi_to_rgb(5),
+        Color::DarkCyan => ansi_to_rgb(6),
+        Color::Grey => ansi_to_rgb(7),
+        Color::DarkGrey => ansi_to_rgb(8),
+        Color::Red => ansi_to_rgb(9),
+        Color::Green => ansi_to_rgb(10),
+        Color::Yellow => ansi_to_rgb(11),
+        Color::Blue => ansi_to_rgb(12),
+        Color::Magenta => ansi_to_rgb(13),
+        Color::Cyan => ansi_to_rgb(14),
+        Color::White | Color::Reset => ansi_to_rgb(15),
+    }
+}
+
+
+/// Converts one of the 256 ANSI colors into RGB values
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    const BASE: [(u8, u8, u8); 16] = [
+        (0, 0, 0), (128, 0, 0), (0, 128, 0), (128, 128, 0),
+        (0, 0, 128), (128, 0, 128), (0, 128, 128), (192, 192, 192),
+        (128, 128, 128), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+        (0, 0, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+    ];
+    match value {
+        0..=15 => BASE[value as usize],
+        // 6x6x6 color cube
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let v = value - 16;
+            (level(v / 36), level((v / 6) % 6), level(v % 6))
+        },
+        // Grayscale ramp
+        232..=255 => {
+            let gray = 8 + (value - 232) * 10;
+            (gray, gray, gray)
+        },
+    }
+}
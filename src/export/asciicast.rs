@@ -0,0 +1,99 @@
+use std::io::Write;
+
+use crossterm::{cursor, terminal::{Clear, ClearType}, queue, style::Color};
+use serde_json::json;
+
+use crate::{render::{Renderer, TerminalRenderer}, Result};
+
+
+/// Records the growth of a tree as an asciicast v2 recording (see https://docs.asciinema.org/manual/asciicast/v2/).
+/// Every flushed batch of draw operations becomes one output event, spaced `frame_time` apart
+pub struct AsciicastRecorder {
+    width: u16,
+    height: u16,
+    /// Time between two events in seconds
+    frame_time: f64,
+    /// Collects the escape sequences of the current batch
+    terminal: TerminalRenderer<Vec<u8>>,
+    /// Timestamp and output of every event
+    events: Vec<(f64, String)>,
+}
+
+impl AsciicastRecorder {
+    /// Creates a recorder for a terminal of the given size.
+    /// `time_scale` is the time between two steps in milliseconds
+    pub fn new(width: u16, height: u16, time_scale: u64) -> AsciicastRecorder {
+        let mut terminal = TerminalRenderer::new(vec![]);
+        // Start the replay on a clean screen without a cursor in the way
+        // Writing into a Vec can't fail
+        let _ = queue!(terminal.out, Clear(ClearType::All), cursor::Hide);
+        AsciicastRecorder {
+            width,
+            height,
+            frame_time: time_scale as f64 / 1000.0,
+            terminal,
+            events: vec![],
+        }
+    }
+
+    /// Writes the header and all recorded events
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let header = json!({
+            "version": 2,
+            "width": self.width,
+            "height": self.height,
+        });
+        writeln!(writer, "{}", header)?;
+        for (time, output) in &self.events {
+            writeln!(writer, "{}", json!([time, "o", output]))?;
+        }
+        Ok(writer.flush()?)
+    }
+}
+
+impl Renderer for AsciicastRecorder {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        self.terminal.draw(pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if self.terminal.out.is_empty() {
+            return Ok(());
+        }
+        let output = String::from_utf8_lossy(&self.terminal.out).into_owned();
+        self.terminal.out.clear();
+        // Round to milliseconds to keep the file small
+        let time = (self.events.len() as f64 * self.frame_time * 1000.0).round() / 1000.0;
+        self.events.push((time, output));
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_header_and_an_event_per_flushed_batch() {
+        let mut recorder = AsciicastRecorder::new(20, 10, 250);
+        recorder.draw((1, 1), "a", Color::Green).unwrap();
+        recorder.flush().unwrap();
+        // Nothing drawn, no event
+        recorder.flush().unwrap();
+        recorder.draw((2, 1), "b", Color::Green).unwrap();
+        recorder.draw((3, 1), "c", Color::Green).unwrap();
+        recorder.flush().unwrap();
+
+        let mut bytes = vec![];
+        recorder.write(&mut bytes).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines[0], json!({ "version": 2, "width": 20, "height": 10 }));
+        assert_eq!(lines.len(), 3);
+        assert_eq!((&lines[1][0], &lines[1][1]), (&json!(0.0), &json!("o")));
+        assert_eq!(lines[2][0], json!(0.25));
+        let second = lines[2][2].as_str().unwrap();
+        assert!(second.contains('b') && second.contains('c') && !second.contains('a'));
+    }
+}
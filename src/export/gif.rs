@@ -0,0 +1,169 @@
+use std::{borrow::Cow, collections::{HashMap, HashSet}, io::Write};
+
+use crossterm::style::Color;
+
+use crate::{canvas::Canvas, render::Renderer, Error, Result};
+use super::{color_to_rgb, export_area, raster::{rasterize_area, RasterOptions}};
+
+
+/// Index of each RGB color in the palette
+type ColorIndices = HashMap<(u8, u8, u8), u8>;
+
+
+/// Records a canvas frame after every flushed step and encodes them as an animated GIF
+pub struct GifRecorder {
+    canvas: Canvas,
+    frames: Vec<Canvas>,
+    /// Time between two frames in milliseconds
+    time_scale: u64,
+    /// How long the last frame is shown before the animation loops, in milliseconds
+    hold: u64,
+}
+
+impl GifRecorder {
+    /// Creates a recorder for a screen of the given size.
+    /// `time_scale` is the time between two steps, `hold` how long the finished tree stays visible (both in milliseconds)
+    pub fn new(width: u16, height: u16, time_scale: u64, hold: u64) -> GifRecorder {
+        GifRecorder {
+            canvas: Canvas::new(width, height),
+            frames: vec![],
+            time_scale,
+            hold,
+        }
+    }
+
+    /// The current picture, after everything drawn so far
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    /// Rasterizes all frames with the built-in bitmap font and writes the animation.
+    /// Every frame covers the area the final picture needs
+    pub fn write<W: Write>(&self, options: &RasterOptions, writer: W) -> Result<()> {
+        let area = export_area(&self.canvas, options.crop, options.padding);
+        let (palette, indices) = self.palette(options.background);
+        let lookup = |rgb: &[u8]| indices[&(rgb[0], rgb[1], rgb[2])];
+
+        let first = rasterize_area(&self.canvas, options, area);
+        let (width, height) = gif_size(first.width, first.height)?;
+        let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        for (idx, frame) in self.frames.iter().enumerate() {
+            let image = rasterize_area(frame, options, area);
+            // Most pixels are background, so remember the last lookup
+            let background = color_bytes(options.background);
+            let mut last = (background, lookup(&background));
+            let buffer: Vec<u8> = image.pixels.chunks(3).map(|rgb| {
+                if rgb != last.0 {
+                    last = ([rgb[0], rgb[1], rgb[2]], lookup(rgb));
+                }
+                last.1
+            }).collect();
+            let delay = match idx + 1 == self.frames.len() {
+                true => self.hold,
+                false => self.time_scale,
+            };
+            let (width, height) = gif_size(image.width, image.height)?;
+            encoder.write_frame(&gif::Frame {
+                width,
+                height,
+                // The delay is given in hundredths of a second
+                delay: (delay / 10).clamp(1, u16::MAX as u64) as u16,
+                buffer: Cow::Owned(buffer),
+                ..Default::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Builds a global palette of at most 256 colors for every color used in any frame.
+    /// Returns the palette and the index of each used color
+    fn palette(&self, background: Color) -> (Vec<u8>, ColorIndices) {
+        let mut colors = vec![color_to_rgb(background)];
+        let mut seen: HashSet<(u8, u8, u8)> = colors.iter().copied().collect();
+        for frame in &self.frames {
+            for cell in frame.rows().flatten().flatten() {
+                let rgb = color_to_rgb(cell.color);
+                if seen.insert(rgb) {
+                    colors.push(rgb);
+                }
+            }
+        }
+
+        if colors.len() <= 256 {
+            let palette = colors.iter().flat_map(|&(r, g, b)| [r, g, b]).collect();
+            let indices = colors.into_iter().enumerate().map(|(idx, rgb)| (rgb, idx as u8)).collect();
+            return (palette, indices);
+        }
+
+        // Too many shades (e.g. from the leaf color variations), reduce them to 256
+        let rgba: Vec<u8> = colors.iter().flat_map(|&(r, g, b)| [r, g, b, 255]).collect();
+        let quantizer = color_quant::NeuQuant::new(10, 256, &rgba);
+        let indices = colors.into_iter().map(|(r, g, b)| ((r, g, b), quantizer.index_of(&[r, g, b, 255]) as u8)).collect();
+        (quantizer.color_map_rgb(), indices)
+    }
+}
+
+impl Renderer for GifRecorder {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        self.canvas.draw(pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.frames.push(self.canvas.clone());
+        Ok(())
+    }
+}
+
+
+/// GIFs store their dimensions as 16 bit numbers and can't be empty
+fn gif_size(width: u32, height: u32) -> Result<(u16, u16)> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(gif_width), Ok(gif_height)) if gif_width > 0 && gif_height > 0 => Ok((gif_width, gif_height)),
+        _ => Err(Error::ImageSize(width, height)),
+    }
+}
+
+fn color_bytes(color: Color) -> [u8; 3] {
+    let (r, g, b) = color_to_rgb(color);
+    [r, g, b]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_two_steps() -> GifRecorder {
+        let mut recorder = GifRecorder::new(5, 3, 30, 1000);
+        recorder.draw((1, 1), "ab", Color::Green).unwrap();
+        recorder.flush().unwrap();
+        recorder.draw((1, 2), "c", Color::Red).unwrap();
+        recorder.flush().unwrap();
+        recorder
+    }
+
+    #[test]
+    fn writes_a_frame_for_every_step() {
+        let options = RasterOptions { crop: false, ..Default::default() };
+        let mut bytes = vec![];
+        record_two_steps().write(&options, &mut bytes).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new().read_info(bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (5 * 12, 3 * 18));
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height), (5 * 12, 3 * 18));
+            delays.push(frame.delay);
+        }
+        assert_eq!(delays, [3, 100]);
+    }
+
+    #[test]
+    fn empty_images_are_refused() {
+        let options = RasterOptions { cell_height: 0, ..Default::default() };
+        let result = record_two_steps().write(&options, vec![]);
+        assert!(matches!(result, Err(Error::ImageSize(_, 0))));
+    }
+}
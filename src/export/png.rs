@@ -0,0 +1,42 @@
+use std::io::Write;
+
+use crate::{canvas::Canvas, Result};
+use super::raster::{rasterize, RasterOptions};
+
+
+/// Renders the canvas with the built-in bitmap font and writes it as a PNG image
+pub fn write_png<W: Write>(canvas: &Canvas, options: &RasterOptions, writer: W) -> Result<()> {
+    let image = rasterize(canvas, options);
+    let mut encoder = png::Encoder::new(writer, image.width, image.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&image.pixels)?;
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Color;
+
+    use super::*;
+    use crate::render::Renderer;
+
+    #[test]
+    fn cropped_image_covers_the_drawing_and_its_padding() {
+        let mut canvas = Canvas::new(10, 5);
+        canvas.draw((4, 2), "ab", Color::Red).unwrap();
+        let mut bytes = vec![];
+        write_png(&canvas, &RasterOptions::default(), &mut bytes).unwrap();
+
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        // Two cells and one cell of padding on every side, 12x18 pixels each
+        assert_eq!((info.width, info.height), (4 * 12, 3 * 18));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert!(pixels.chunks(3).any(|rgb| rgb == [255, 0, 0]));
+    }
+}
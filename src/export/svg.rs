@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use crossterm::style::Color;
+
+use crate::canvas::Canvas;
+use super::{color_to_rgb, export_area};
+
+
+/// Options for `to_svg`
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    /// Font size in pixels. A cell is 0.6 font sizes wide and 1.2 font sizes high
+    pub font_size: f64,
+    /// Background color, transparent if `None`
+    pub background: Option<Color>,
+    /// Only export the part of the canvas which contains something visible
+    pub crop: bool,
+    /// Empty cells around the picture when cropping
+    pub padding: u16,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            font_size: 16.0,
+            background: Some(Color::Black),
+            crop: true,
+            padding: 1,
+        }
+    }
+}
+
+
+/// Lays out every cell of the canvas as monospaced text in an SVG image
+pub fn to_svg(canvas: &Canvas, options: &SvgOptions) -> String {
+    let (min_x, min_y, max_x, max_y) = export_area(canvas, options.crop, options.padding);
+    let cell_width = options.font_size * 0.6;
+    let cell_height = options.font_size * 1.2;
+    let width = (max_x - min_x + 1) as f64 * cell_width;
+    let height = (max_y - min_y + 1) as f64 * cell_height;
+
+    let mut svg = String::new();
+    let (width, height) = (px(width), px(height));
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#).unwrap();
+    if let Some(background) = options.background {
+        writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, to_hex(background)).unwrap();
+    }
+    writeln!(svg, r#"<g font-family="monospace" font-size="{}" xml:space="preserve">"#, options.font_size).unwrap();
+
+    for y in min_y..=max_y {
+        // Baseline of the text, a bit above the bottom of the cell
+        let baseline = px((y - min_y) as f64 * cell_height + options.font_size);
+        let mut x = min_x;
+        while x <= max_x {
+            let cell = match canvas.get(x, y) {
+                Some(cell) if cell.symbol != ' ' && !cell.is_continuation() => cell,
+                _ => {
+                    x += 1;
+                    continue;
+                },
+            };
+            // Collect a run of equally colored characters into one text element
+            let start = x;
+            let mut run = String::new();
+            while let Some(next) = canvas.get(x, y).filter(|c| x <= max_x && c.symbol != ' ' && c.color == cell.color) {
+                // The second half of a wide character only adds to the length of the run
+                if !next.is_continuation() {
+                    push_escaped(&mut run, next.symbol);
+                }
+                x += 1;
+            }
+            let length = px((x - start) as f64 * cell_width);
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" fill="{}" textLength="{}" lengthAdjust="spacingAndGlyphs">{}</text>"#,
+                px((start - min_x) as f64 * cell_width),
+                baseline,
+                to_hex(cell.color),
+                length,
+                run,
+            ).unwrap();
+        }
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+
+/// Rounds a pixel value to keep the file readable
+fn px(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+
+fn to_hex(color: Color) -> String {
+    let (r, g, b) = color_to_rgb(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+
+fn push_escaped(text: &mut String, symbol: char) {
+    match symbol {
+        '&' => text.push_str("&amp;"),
+        '<' => text.push_str("&lt;"),
+        '>' => text.push_str("&gt;"),
+        _ => text.push(symbol),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::Renderer;
+
+    #[test]
+    fn escapes_markup_and_stretches_wide_characters_over_two_cells() {
+        let mut canvas = Canvas::new(6, 1);
+        canvas.draw((0, 0), "a&<", Color::Red).unwrap();
+        canvas.draw((3, 0), "🌸", Color::Green).unwrap();
+        let options = SvgOptions { background: None, crop: false, ..Default::default() };
+        let svg = to_svg(&canvas, &options);
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="57.6" height="19.2" viewBox="0 0 57.6 19.2">"#));
+        assert!(!svg.contains("<rect"));
+        assert!(svg.contains(r##"<text x="0" y="16" fill="#ff0000" textLength="28.8" lengthAdjust="spacingAndGlyphs">a&amp;&lt;</text>"##));
+        assert!(svg.contains(r##"<text x="28.8" y="16" fill="#00ff00" textLength="19.2" lengthAdjust="spacingAndGlyphs">🌸</text>"##));
+    }
+}
@@ -0,0 +1,108 @@
+use crossterm::style::Color;
+
+use crate::{region::Region, render::Renderer, BonsaiTree, Error, Result, MIN_SIZE};
+
+
+/// Several trees growing side by side, each in its own region of the screen.
+/// They are stepped together, so they grow at the same time
+pub struct Forest {
+    trees: Vec<BonsaiTree>,
+}
+
+impl Forest {
+    pub fn new(trees: Vec<BonsaiTree>) -> Forest {
+        Forest { trees }
+    }
+
+    /// Splits `region` into `count` adjacent regions of (almost) the same width, from left to right.
+    /// Fails if the regions would be narrower than `MIN_SIZE`
+    pub fn split(region: Region, count: usize) -> Result<Vec<Region>> {
+        let count = count.max(1);
+        if region.width.max(0) as usize / count < MIN_SIZE.0 as usize {
+            return Err(Error::TerminalTooSmall {
+                width: region.width.max(0) as u16,
+                height: region.height.max(0) as u16,
+                min_width: (MIN_SIZE.0 as usize * count).min(u16::MAX as usize) as u16,
+                min_height: MIN_SIZE.1,
+            });
+        }
+        // Fits into an i16 now, the regions are at least MIN_SIZE.0 wide
+        let count = count as i16;
+        let (width, rest) = (region.width / count, region.width % count);
+        let mut x = region.x;
+        Ok((0..count).map(|idx| {
+            // The columns which don't divide evenly go to the first regions
+            let width = width + i16::from(idx < rest);
+            let part = Region::new(x, region.y, width, region.height);
+            x += width;
+            part
+        }).collect())
+    }
+
+    /// Grows every tree by one step, see `BonsaiTree::step`.
+    /// All trees draw before the renderer gets flushed once, so a step is still a single frame.
+    /// Returns if any tree was able to grow
+    pub fn step<R: Renderer + ?Sized>(&mut self, renderer: &mut R) -> Result<bool> {
+        let mut did_grow = false;
+        for tree in &mut self.trees {
+            if tree.step(&mut Unflushed(&mut *renderer))? {
+                did_grow = true;
+            }
+        }
+        if did_grow {
+            renderer.flush()?;
+        }
+        Ok(did_grow)
+    }
+
+    /// Returns if every tree has finished growing
+    pub fn is_complete(&self) -> bool {
+        self.trees.iter().all(BonsaiTree::is_complete)
+    }
+
+    /// Steps the trees until all of them are complete, but at most `max_steps` times.
+    /// Returns if all trees are complete
+    pub fn grow_to_completion<R: Renderer + ?Sized>(&mut self, renderer: &mut R, max_steps: usize) -> Result<bool> {
+        for _ in 0..max_steps {
+            if !self.step(renderer)? {
+                break;
+            }
+        }
+        Ok(self.is_complete())
+    }
+
+    /// Repaints all trees, see `BonsaiTree::redraw`
+    pub fn redraw<R: Renderer + ?Sized>(&self, renderer: &mut R) -> Result<()> {
+        for tree in &self.trees {
+            tree.redraw(renderer)?;
+        }
+        Ok(())
+    }
+
+    /// The smallest region containing the regions of all trees
+    pub fn region(&self) -> Region {
+        let x = self.trees.iter().map(|tree| tree.region.x).min().unwrap_or(0);
+        let y = self.trees.iter().map(|tree| tree.region.y).min().unwrap_or(0);
+        let right = self.trees.iter().map(|tree| tree.region.right()).max().unwrap_or(0);
+        let bottom = self.trees.iter().map(|tree| tree.region.bottom()).max().unwrap_or(0);
+        Region::new(x, y, right - x, bottom - y)
+    }
+
+    pub fn trees(&self) -> &[BonsaiTree] {
+        &self.trees
+    }
+}
+
+
+/// Passes draws through but leaves flushing to the forest
+struct Unflushed<'a, R: Renderer + ?Sized>(&'a mut R);
+
+impl<R: Renderer + ?Sized> Renderer for Unflushed<'_, R> {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        self.0.draw(pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -1,18 +1,43 @@
-use std::{io::{Write}};
-use crossterm::{style::{Stylize, self, Color}, cursor, queue, execute, terminal::Clear};
+use std::io::Write;
+use crossterm::{style::{Stylize, self, Color}, cursor, queue};
 use rand::{seq::SliceRandom, Rng};
 use rand_chacha::ChaCha8Rng;
 use simple_simplex::NoiseConfig;
 pub mod branch;
-use branch::{BonsaiBranch, Direction, BranchShape};
+use branch::{BonsaiBranch, Direction, BranchShape, Style};
 pub mod point;
 use point::Point;
+pub mod region;
+pub mod forest;
+use region::Region;
 pub mod appearance;
-use appearance::{TreeAppearance};
+use appearance::TreeAppearance;
+pub mod config;
+use config::Config;
+pub mod species;
+pub mod render;
+pub mod canvas;
+pub mod document;
+pub mod export;
+pub mod session;
+pub mod error;
+pub use error::{Error, Result};
+use render::Renderer;
 
 
 const BROWN: Color = Color::Rgb {r: 142, g: 44, b: 19};
 const ROSE: Color = Color::Rgb { r: 252, g: 212, b: 251 };
+/// Space between the trunk and the outer walls of the base
+const BASE_MARGIN: usize = 4;
+/// Trees on screens at least this large (columns, rows) are grown at full size, smaller screens get smaller trees
+const FULL_SIZE: (i16, i16) = (80, 24);
+/// How far trees are scaled down at most
+const MIN_SCALE: f32 = 0.25;
+/// Smallest screen (columns, rows) which still fits a tree at `MIN_SCALE`,
+/// including the tallest base and the caption row below it
+pub const MIN_SIZE: (u16, u16) = (16, 11);
+/// Default cap for `BonsaiTree::grow_to_completion`, far more than any tree needs
+pub const MAX_GROWTH_STEPS: usize = 10_000;
 pub type RNG = ChaCha8Rng;
 pub type Writer = std::io::BufWriter<std::io::Stdout>;
 
@@ -21,49 +46,81 @@ pub struct BonsaiTree {
     pub noise: NoiseConfig,
     pub rng: RNG,
     seed: u64,
-    pub stdout: Writer,
-    pub width: i16,
-    pub height: i16,
+    /// Part of the screen the tree grows in
+    pub region: Region,
     branches: Vec<BonsaiBranch>,
     pub appearance: TreeAppearance,
+    /// Set once a step could not grow any branch
+    complete: bool,
+    /// Number of steps taken so far
+    age: usize,
 }
 
 impl BonsaiTree {
-    /// Creates a new randomized tree with the given values
-    pub fn new(noise: NoiseConfig, mut rng: RNG, seed: u64, stdout: Writer, width: i16, height: i16, trunk_width: usize) -> BonsaiTree {
-        let appearance = TreeAppearance::randomize(&mut rng, trunk_width);
+    /// Creates a new randomized tree with the given values and the built-in config, growing on the whole screen
+    pub fn new(noise: NoiseConfig, rng: RNG, seed: u64, width: i16, height: i16, trunk_width: usize) -> Result<BonsaiTree> {
+        BonsaiTree::in_region(noise, rng, seed, Region::screen(width, height), trunk_width, &Config::default())
+    }
+
+
+    /// Creates a new randomized tree which grows inside `region`, with its base at the bottom of it.
+    /// Random choices are made within the bounds of `config`.
+    /// In regions smaller than `FULL_SIZE` the trunk, branches and leaves are scaled down to fit.
+    /// Fails if the region is smaller than `MIN_SIZE` or `config` is invalid
+    pub fn in_region(noise: NoiseConfig, mut rng: RNG, seed: u64, region: Region, trunk_width: usize, config: &Config) -> Result<BonsaiTree> {
+        config.validate()?;
+        let Region { width, height, .. } = region;
+        if width < MIN_SIZE.0 as i16 || height < MIN_SIZE.1 as i16 {
+            return Err(Error::TerminalTooSmall {
+                width: width.max(0) as u16,
+                height: height.max(0) as u16,
+                min_width: MIN_SIZE.0,
+                min_height: MIN_SIZE.1,
+            });
+        }
+        let scale = (width as f32 / FULL_SIZE.0 as f32)
+            .min(height as f32 / FULL_SIZE.1 as f32)
+            .clamp(MIN_SCALE, 1.0);
+        let margin = base_margin(scale);
+        // The trunk has to fit between the walls of the base
+        let max_trunk_width = width as usize - 2 * margin - 4;
+        let trunk_width = ((trunk_width as f32 * scale).round() as usize).clamp(1, max_trunk_width);
+        let mut appearance = TreeAppearance::randomize(&mut rng, trunk_width, config);
+        appearance.scale = scale;
         
-        let baseheight = appearance.get_base(4).lines().count();
-        let mut branches = vec![];
+        let baseheight = appearance.get_base(margin).lines().count();
         let w = appearance.trunk_width;
         // Center the tree trunk in the horizontal axis and above the base (plant pot)
-        let start = Point { x: width / 2 - (w as f32 / 2.0 as f32).round() as i16, y: height - baseheight as i16};
-        branches.push(
-            BonsaiBranch::new(
-                start,
-                Direction::Up,
-                w,
-                BranchShape::default_trunk(),
-                BROWN,
-                appearance.leaf_count,
-                appearance.leaf_type,
-                appearance.leaf_color
-            )
-        );
-        BonsaiTree {
+        let start = Point {
+            x: region.x + width / 2 - (w as f32 / 2.0).round() as i16,
+            y: growth_region(&region).bottom() - baseheight as i16,
+        };
+        let branches = vec![new_branch(&appearance, start, Direction::Up, w, appearance.trunk_shape)];
+        Ok(BonsaiTree {
             noise,
             branches,
             rng,
             seed,
-            stdout,
-            width,
-            height,
+            region,
             appearance,
-        }
+            complete: false,
+            age: 0,
+        })
     }
 
 
-    pub fn step(&mut self) {
+    /// Grows every branch by one step and emits the resulting glyphs through `renderer`.
+    /// Returns if any branch was able to grow. Once it returns false the tree is complete
+    /// and further calls do nothing
+    pub fn step<R: Renderer + ?Sized>(&mut self, renderer: &mut R) -> Result<bool> {
+        if self.complete {
+            return Ok(false);
+        }
+        // The trunk only has its starting point before the first step
+        if self.branches[0].steps.len() == 1 {
+            self.draw_base(renderer)?;
+            draw_caption(renderer, self.seed, &self.region)?;
+        }
         let mut max_branch_height = 0;
         let mut max_branch_dir = Direction::Up;
         let mut did_grow = false;
@@ -73,66 +130,177 @@ impl BonsaiTree {
                 max_branch_dir = branch.direction.clone();
             }
             let g = branch.step(
+                &self.appearance,
                 &self.noise,
                 &mut self.rng,
-                (self.width, self.height),
-                &mut self.stdout);
+                &growth_region(&self.region),
+                renderer)?;
             if g {
                 did_grow = true;
             }
         }
         let last_trunk_step = self.branches[0].steps.last().unwrap();
-        let mut ratio = 1.0 - (last_trunk_step.pos.y as f32 / (self.height - 1) as f32);
-        if ratio < 0.0 {
-            ratio = 0.0;
-        } else if ratio > 1.0 {
-            ratio = 1.0;
-        };
-        let dir = vec![Direction::Left, Direction::Right]
+        let ratio = self.branches[0].growth_ratio(&self.appearance, &growth_region(&self.region)).clamp(0.0, 1.0);
+        let mut dir = [Direction::Left, Direction::Right]
             .choose(&mut self.rng)
             .unwrap()
             .clone();
+        let mut branching = self.appearance.branching;
+        match self.appearance.style {
+            // Most branches grow with the wind
+            Style::Windswept => branching.bias = 0.7 * self.appearance.side as f64,
+            // Branches mostly hang over the side the trunk falls to
+            Style::Cascade => branching.bias = 0.5 * self.appearance.side as f64,
+            // The lower part of the trunk stays bare
+            Style::Literati => branching.start = branching.start.max(0.7),
+            _ => {},
+        }
+        // Branches on the other side than the favored one move over with the strength of the bias
+        let favored = match branching.bias < 0.0 {
+            true => Direction::Left,
+            false => Direction::Right,
+        };
+        if branching.bias != 0.0 && dir != favored && self.rng.gen_bool(branching.bias.abs()) {
+            dir = favored;
+        }
         let default_width = last_trunk_step.width;
-        let min_ratio = 0.35;
+        let spacing = (default_width as f32 * branching.spacing).round() as i16;
         // Only spawn branch if it has some distance to the other branches and its towards the top
-        if default_width > 1 && ratio > min_ratio && ratio < 1.0 && ((dir == max_branch_dir && (last_trunk_step.pos.y - max_branch_height).abs() > default_width as i16) || (dir != max_branch_dir && self.rng.gen_bool(ratio as f64))) {
+        if default_width > 1 && ratio > branching.start && ratio < 1.0 && ((dir == max_branch_dir && (last_trunk_step.pos.y - max_branch_height).abs() > spacing) || (dir != max_branch_dir && self.rng.gen_bool((ratio as f64 * branching.frequency).min(1.0)))) {
             let mut b_width = default_width;
             if last_trunk_step.width <= 2 {
                 b_width = 1;
             };
-            self.branches.push(BonsaiBranch::new(
-                last_trunk_step.pos,
-                dir,
-                b_width as usize,
-                BranchShape::default_branch(),
-                BROWN,
-                self.appearance.leaf_count,
-                self.appearance.leaf_type,
-                self.appearance.leaf_color)
-            );
+            let mut branch = new_branch(&self.appearance, last_trunk_step.pos, dir, b_width, self.appearance.branch_shape);
+            // It grows for the first time in the next step
+            branch.born = self.age + 1;
+            self.branches.push(branch);
         }
+        self.age += 1;
         //println!("{}", self.branches.len());
         
         if did_grow {
-            draw(&mut self.stdout, (1, self.height as u16 - 2), format!("Seed: {}", self.seed).as_str(), Color::DarkGrey);
-            self.flush();
+            renderer.flush()?;
+        } else {
+            self.complete = true;
+        }
+        Ok(did_grow)
+    }
+
+
+    /// Returns if the tree has finished growing
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+
+    /// Steps the tree until it is complete, but at most `max_steps` times.
+    /// Returns if the tree is complete
+    pub fn grow_to_completion<R: Renderer + ?Sized>(&mut self, renderer: &mut R, max_steps: usize) -> Result<bool> {
+        for _ in 0..max_steps {
+            if !self.step(renderer)? {
+                break;
+            }
         }
+        Ok(self.complete)
+    }
+
+
+    /// Repaints the whole tree, including its base and caption, from the stored steps and leaves.
+    /// Gives the same picture as growing it did, e.g. after the screen was cleared
+    pub fn redraw<R: Renderer + ?Sized>(&self, renderer: &mut R) -> Result<()> {
+        draw_tree(&self.appearance, self.seed, &self.region, &self.branches, renderer)
+    }
+
+
+    /// Draws the base (plant pot) so that the gap in its rim sits under the trunk
+    pub fn draw_base<R: Renderer + ?Sized>(&self, renderer: &mut R) -> Result<()> {
+        self.appearance.draw_base(base_margin(self.appearance.scale), self.branches[0].steps[0].pos, renderer)
     }
 
 
-    /// Helper function to flush the screen
-    pub fn flush(&mut self) {
-        self.stdout.flush().unwrap();
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
+
+
+    pub fn branches(&self) -> &[BonsaiBranch] {
+        &self.branches
+    }
+}
+
+
+
+/// Creates a branch which looks like the rest of the tree
+fn new_branch(appearance: &TreeAppearance, start: Point<i16>, direction: Direction, width: usize, shape: BranchShape) -> BonsaiBranch {
+    BonsaiBranch::new(
+        start,
+        direction,
+        width,
+        BranchShape { scale: appearance.scale, ..shape },
+        appearance.trunk_color,
+        appearance.leaf_count,
+        appearance.leaf_type,
+        appearance.leaf_color
+    )
 }
 
 
+/// Draws a whole grown tree. Every tree step each branch drew one step or leaf,
+/// so replaying them in the same order reproduces where they overlap
+fn draw_tree<R: Renderer + ?Sized>(appearance: &TreeAppearance, seed: u64, region: &Region, branches: &[BonsaiBranch], renderer: &mut R) -> Result<()> {
+    if let Some(trunk) = branches.first() {
+        appearance.draw_base(base_margin(appearance.scale), trunk.steps[0].pos, renderer)?;
+    }
+    draw_caption(renderer, seed, region)?;
+
+    let end = branches.iter().map(|branch| branch.born + branch.drawn_items()).max().unwrap_or(0);
+    for age in 0..end {
+        for branch in branches.iter().filter(|branch| branch.born <= age) {
+            branch.draw_item(age - branch.born, renderer)?;
+        }
+    }
+    renderer.flush()
+}
+
+
+/// Space between the trunk and the outer walls of the base for trees scaled by `scale`.
+/// Never less than 2, the narrowest base needs that much
+fn base_margin(scale: f32) -> usize {
+    ((BASE_MARGIN as f32 * scale).round() as usize).max(2)
+}
+
+
+/// The part of the region the tree grows in, everything but the caption row at the bottom
+fn growth_region(region: &Region) -> Region {
+    Region::new(region.x, region.y, region.width, region.height - 1)
+}
+
+
+/// Draws the seed in the bottom left corner of the region, below the base.
+/// Narrow regions only get the number, cut off at the right edge so it doesn't run into trees next to it
+fn draw_caption<R: Renderer + ?Sized>(renderer: &mut R, seed: u64, region: &Region) -> Result<()> {
+    let space = (region.width - 1).max(0) as usize;
+    let caption = match format!("Seed: {}", seed) {
+        caption if caption.len() <= space => caption,
+        _ => seed.to_string().chars().take(space).collect(),
+    };
+    renderer.draw(((region.x + 1) as u16, (region.bottom() - 1) as u16), &caption, Color::DarkGrey)
+}
+
+
+/// Parses the kebab-case name serde uses for a variant of `T`, like `weeping-willow`.
+/// The error lists all names, so `FromStr` of the enums can use it for command line arguments
+pub(crate) fn parse_name<T: serde::de::DeserializeOwned>(name: &str) -> std::result::Result<T, String> {
+    T::deserialize(serde::de::value::StrDeserializer::<serde::de::value::Error>::new(name)).map_err(|err| err.to_string())
+}
+
 
 /// Helper function to draw anything on the screen at a specified position
-pub fn draw(stdout: &mut Writer, pos: (u16, u16), what: &str, color: Color) {
-    //return;
+pub fn draw<W: Write>(stdout: &mut W, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
     queue!(stdout,
-        cursor::MoveTo(pos.0.into(), pos.1.into()),
+        cursor::MoveTo(pos.0, pos.1),
         style::PrintStyledContent(what.with(color))
-    ).unwrap();
+    )?;
+    Ok(())
 }
\ No newline at end of file
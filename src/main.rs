@@ -1,16 +1,16 @@
-use std::{u64::MAX, time::{Duration, Instant}, io::{Cursor, Write, BufWriter}};
-use bonsai::{BonsaiTree, Writer};
-use crossterm::{execute, terminal::{Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen}, cursor, style::{Color, self, Stylize}, event::{poll, read, Event, KeyCode, KeyModifiers, EnableMouseCapture}};
-use rand::{Rng, SeedableRng, rngs::ThreadRng};
+use std::{time::{Duration, Instant}, path::{Path, PathBuf}, io::Write, process::ExitCode};
+use bonsai::{BonsaiTree, Error, appearance::BaseType, branch::{LeafType, Style, TrunkGlyphs}, config::{parse_color, Config}, species::Species, region::Region, forest::Forest, render::TerminalRenderer, session::TerminalSession, canvas::Canvas, document::TreeDocument, export::{svg, png, raster::RasterOptions, asciicast::AsciicastRecorder, gif::GifRecorder}, MAX_GROWTH_STEPS};
+use crossterm::{cursor, execute, queue, terminal::{Clear, ClearType}, event::{poll, read, Event, KeyCode, KeyModifiers}, style::Color};
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use simple_simplex::NoiseConfig;
 use clap::Parser;
 
 
-#[derive(Parser, Debug, Clone, Copy)]
+#[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    /// Starting with of the trunk
+    /// Starting with of the trunk, random within the range of the config file if not given
     #[clap(short, long, value_parser, default_value_t = 0)]
     width: usize,
     /// How fast the tree will grow
@@ -19,79 +19,429 @@ struct Args {
     /// Seed of the tree. Same seeds produce same trees
     #[clap(short, long, value_parser, default_value_t = 0)]
     seed: u64,
+    /// Grow the tree instantly, print it to stdout and exit
+    #[clap(short, long)]
+    print: bool,
+    /// Leave out the colors when printing the tree
+    #[clap(long, requires = "print")]
+    plain: bool,
+    /// Grow the tree instantly and save it as JSON or RON (by file extension)
+    #[clap(long, value_name = "PATH")]
+    export: Option<PathBuf>,
+    /// Show a tree saved with --export instead of growing a new one
+    #[clap(long, value_name = "PATH", conflicts_with_all = ["export", "infinite"])]
+    import: Option<PathBuf>,
+    /// Grow the tree instantly and save it as an SVG image
+    #[clap(long, value_name = "PATH")]
+    svg: Option<PathBuf>,
+    /// Grow the tree instantly and save it as a PNG image
+    #[clap(long, value_name = "PATH")]
+    png: Option<PathBuf>,
+    /// Width of a character cell in PNG and GIF images (in pixels)
+    #[clap(long, value_parser, default_value_t = 12)]
+    cell_width: u32,
+    /// Height of a character cell in PNG and GIF images (in pixels)
+    #[clap(long, value_parser, default_value_t = 18)]
+    cell_height: u32,
+    /// Grow the tree instantly and save its growth as an asciinema recording, timed by --time-scale
+    #[clap(long, value_name = "PATH", conflicts_with = "import")]
+    cast: Option<PathBuf>,
+    /// Grow the tree instantly and save its growth as an animated GIF, timed by --time-scale
+    #[clap(long, value_name = "PATH", conflicts_with = "import")]
+    gif: Option<PathBuf>,
+    /// How long the finished tree is shown at the end of a GIF (in milliseconds)
+    #[clap(long, value_parser, default_value_t = 3000)]
+    hold: u64,
+    /// Background color of exported images, as a name like `black` or as `#rrggbb`
+    #[clap(long, value_parser = parse_color, default_value = "black")]
+    background: Color,
+    /// Keep growing new trees after each one completes, until a key is pressed
+    #[clap(short, long, conflicts_with_all = ["print", "export", "svg", "png", "cast", "gif"])]
+    infinite: bool,
+    /// Draw on the main screen instead of the alternate one and leave the tree visible after quitting
+    #[clap(short, long)]
+    keep: bool,
+    /// Grow trees like this species, replacing the growth and leaf settings of the config file, e.g. `weeping-willow`
+    #[clap(long)]
+    species: Option<Species>,
+    /// Bonsai style, shaping how the trunk grows, e.g. `windswept`
+    #[clap(long)]
+    style: Option<Style>,
+    /// Always use this type of leaves, e.g. `blossom`
+    #[clap(long)]
+    leaf_type: Option<LeafType>,
+    /// Always use this leaf color, as a name like `green` or as `#rrggbb`
+    #[clap(long, value_parser = parse_color)]
+    leaf_color: Option<Color>,
+    /// Always grow leaves on this many steps from the tip of each branch
+    #[clap(long, value_parser = clap::value_parser!(u16).range(1..))]
+    leaf_count: Option<u16>,
+    /// Always use this base, e.g. `footed-pot`
+    #[clap(long)]
+    base: Option<BaseType>,
+    /// Color of the trunk and the branches, as a name like `dark_yellow` or as `#rrggbb`
+    #[clap(long, value_parser = parse_color)]
+    trunk_color: Option<Color>,
+    /// Leaves to pick from instead of the ones of the leaf type, separated by commas, e.g. `🌸,❀,♣`
+    #[clap(long, value_name = "LEAVES", value_delimiter = ',', value_parser = parse_glyphs)]
+    leaf_glyphs: Option<Vec<String>>,
+    /// Characters the trunk and the branches are drawn with in every direction, e.g. `#=`
+    #[clap(long, value_name = "CHARS", value_parser = parse_glyphs)]
+    trunk_glyphs: Option<String>,
+    /// Config file with defaults for the appearance and growth of trees.
+    /// Defaults to `$XDG_CONFIG_HOME/bonsai/config.toml` or `~/.config/bonsai/config.toml`
+    #[clap(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Number of trees growing side by side
+    #[clap(short, long, default_value_t = 1, value_parser = clap::value_parser!(u16).range(1..=MAX_COUNT), conflicts_with_all = ["export", "import"])]
+    count: u16,
+    /// Grow the tree in a part of the screen starting at this column and row, e.g. `10,2`
+    #[clap(long, value_name = "COL,ROW", value_parser = |value: &str| parse_pair(value, ','))]
+    offset: Option<(u16, u16)>,
+    /// Size of the part of the screen the tree grows in, e.g. `40x15`. Defaults to the rest of the screen
+    #[clap(long, value_name = "COLSxROWS", value_parser = |value: &str| parse_pair(value, 'x'))]
+    size: Option<(u16, u16)>,
+    /// How long a completed tree stays on screen in infinite mode (in milliseconds)
+    #[clap(long, value_parser, default_value_t = 4000)]
+    pause: u64,
 }
 
 
-fn main() {
+impl Args {
+    /// Whether the tree should be finished instantly and written to stdout or files
+    fn is_headless(&self) -> bool {
+        self.print || self.export.is_some() || self.svg.is_some() || self.png.is_some() || self.cast.is_some() || self.gif.is_some()
+    }
+}
+
+
+/// Checks leaves or trunk characters given as flags, which have to be drawable
+fn parse_glyphs(value: &str) -> Result<String, String> {
+    match value {
+        "" => Err("glyphs can't be empty".to_string()),
+        _ if value.chars().any(char::is_control) => Err("glyphs can't contain control characters".to_string()),
+        _ => Ok(value.to_string()),
+    }
+}
+
+
+/// Parses two numbers separated by `separator`, like `10,2` or `40x15`
+fn parse_pair(value: &str, separator: char) -> Result<(u16, u16), String> {
+    let invalid = || format!("invalid value `{}`, expected two numbers separated by `{}`", value, separator);
+    let (first, second) = value.split_once(separator).ok_or_else(invalid)?;
+    match (first.trim().parse(), second.trim().parse()) {
+        (Ok(first), Ok(second)) => Ok((first, second)),
+        _ => Err(invalid()),
+    }
+}
+
+
+/// Screen size to use when the terminal size can't be queried (e.g. when piping the output)
+const FALLBACK_SIZE: (u16, u16) = (80, 24);
+/// Most trees --count grows side by side. Each one needs a few columns, so more never fit anyway
+const MAX_COUNT: i64 = 256;
+
+
+fn main() -> ExitCode {
     let args = Args::parse();
-    let tree_stdout = BufWriter::new(std::io::stdout());
-    let mut tree = grow_bonsai(args, tree_stdout);
-    
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        // Someone stopped reading, e.g. `bonsai -p | head`
+        Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("bonsai: {}", err);
+            exit_code(&err)
+        },
+    }
+}
+
+
+/// Exit code for each kind of error, so scripts can tell them apart
+fn exit_code(err: &Error) -> ExitCode {
+    match err {
+        Error::NotATerminal => ExitCode::from(3),
+        Error::TerminalTooSmall { .. } => ExitCode::from(4),
+        _ => ExitCode::FAILURE,
+    }
+}
+
+
+fn run(args: &Args) -> bonsai::Result<()> {
+    let config = with_flags(args, match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_default()?,
+    });
+    if args.is_headless() {
+        return run_headless(args, &config);
+    }
+    let _session = TerminalSession::start(!args.keep)?;
     let mut stdout = std::io::stdout();
-    execute!(stdout, Clear(ClearType::All)).unwrap();
-    //crossterm::execute!(stdout, EnterAlternateScreen).unwrap();
-    //crossterm::terminal::enable_raw_mode().unwrap();
-    //execute!(stdout, EnableMouseCapture).unwrap();
+    if let Some(path) = &args.import {
+        execute!(stdout, Clear(ClearType::All))?;
+        return show_document(&load_document(path)?);
+    }
 
+    let mut renderer = TerminalRenderer::stdout();
+    let mut forest = grow_forest(args, &config, crossterm::terminal::size()?)?;
+    clear(&mut stdout, &forest.region())?;
+
+    let step_interval = Duration::from_millis(args.time_scale);
+    let pause = Duration::from_millis(args.pause);
     let mut last_step = Instant::now();
+    let mut completed_at = None;
+    // Set while the screen got resized below the minimum size, the trees are kept until it grows again
+    let mut too_small = false;
     loop {
         let time_since_last_step = last_step.elapsed();
-        if time_since_last_step > Duration::from_millis(args.time_scale) {
+        if !too_small && !forest.is_complete() && time_since_last_step > step_interval {
             //, 
             //execute!(stdout, crossterm::terminal::BeginSynchronizedUpdate).unwrap();
-            tree.step();
+            forest.step(&mut renderer)?;
             last_step = Instant::now();
             //execute!(stdout, crossterm::terminal::EndSynchronizedUpdate).unwrap();
         }
-        
-        if poll(Duration::from_millis(0)).unwrap() {
+
+        if !too_small && args.infinite && forest.is_complete() {
+            let completed = *completed_at.get_or_insert_with(Instant::now);
+            if completed.elapsed() >= pause {
+                // Only the first trees use the given seed, the following ones should differ
+                clear(&mut stdout, &forest.region())?;
+                forest = grow_forest(&Args { seed: 0, ..args.clone() }, &config, crossterm::terminal::size()?)?;
+                completed_at = None;
+            }
+        }
+
+        // Wait for input until the next step is due, or just for input once the trees are complete
+        let timeout = match (too_small || forest.is_complete(), completed_at) {
+            (true, Some(completed)) => pause.saturating_sub(completed.elapsed()),
+            (true, None) => Duration::from_secs(1),
+            (false, _) => step_interval.saturating_sub(last_step.elapsed()),
+        };
+        if poll(timeout)? {
             // It's guaranteed that the `read()` won't block when the `poll()`
             // function returns `true`
-            match read().unwrap() {
+            match read()? {
                 // Event::FocusGained => println!("FocusGained"),
                 // Event::FocusLost => println!("FocusLost"),
                 Event::Key(event) => {
+                    if args.infinite {
+                        break;
+                    }
                     if event.modifiers.contains(KeyModifiers::CONTROL) && event.code == KeyCode::Char('c') {
                         break;
                     }
+                    // Repaint whatever got lost, e.g. when another program wrote to the terminal
+                    if event.modifiers.contains(KeyModifiers::CONTROL) && event.code == KeyCode::Char('l') && !too_small {
+                        clear(&mut stdout, &forest.region())?;
+                        forest.redraw(&mut renderer)?;
+                        continue;
+                    }
                     match event.code {
                         KeyCode::Esc => break,
-                        KeyCode::Char('r') => {
-                            crossterm::execute!(stdout, Clear(ClearType::All)).unwrap();
-                            tree = grow_bonsai(args, tree.stdout);
+                        KeyCode::Char('r') if !too_small => {
+                            clear(&mut stdout, &forest.region())?;
+                            forest = grow_forest(args, &config, crossterm::terminal::size()?)?;
                         },
-                        _ => println!("{:?}", event),
+                        _ => (),
                     }
                 },
                 // Event::Mouse(event) => println!("{:?}", event),
                 // #[cfg(feature = "bracketed-paste")]
                 // Event::Paste(data) => println!("Pasted {:?}", data),
-                // Event::Resize(width, height) => println!("New size {}x{}", width, height),
+                Event::Resize(width, height) => {
+                    // The old picture doesn't fit anymore, grow the same trees again at the new size
+                    let was_complete = forest.is_complete();
+                    match too_small {
+                        true => execute!(stdout, Clear(ClearType::All))?,
+                        false => clear(&mut stdout, &forest.region().clamp_to(width as i16, height as i16))?,
+                    }
+                    match grow_forest(&Args { seed: forest.trees()[0].seed(), ..args.clone() }, &config, (width, height)) {
+                        Ok(resized) => {
+                            forest = resized;
+                            too_small = false;
+                        },
+                        // Wait for the screen to grow again instead of quitting
+                        Err(err @ Error::TerminalTooSmall { .. }) => {
+                            execute!(stdout, Clear(ClearType::All), cursor::MoveTo(0, 0), crossterm::style::Print(&err))?;
+                            too_small = true;
+                        },
+                        Err(err) => return Err(err),
+                    }
+                    if was_complete && !too_small {
+                        forest.grow_to_completion(&mut renderer, MAX_GROWTH_STEPS)?;
+                    }
+                    completed_at = None;
+                },
                 _ => (),
             }
-        } else {
-            
         }
     }
-    crossterm::execute!(std::io::stdout(), cursor::MoveTo(0, 0)).unwrap();
-    crossterm::terminal::disable_raw_mode().unwrap();
-    crossterm::execute!(std::io::stdout(), Clear(ClearType::All)).unwrap();
-    //crossterm::execute!(stdout, LeaveAlternateScreen).unwrap();
+    Ok(())
 }
 
 
-/// Sets up the growth of a new bonsai tree
-fn grow_bonsai(args: Args, stdout: Writer) -> BonsaiTree {
-    let mut rng = rand::thread_rng();
-    //let mut stdout = std::io::stdout();
+/// Puts the species and the appearance flags on top of the config file
+fn with_flags(args: &Args, mut config: Config) -> Config {
+    if let Some(species) = args.species {
+        species.apply(&mut config);
+    }
+    config.style = args.style.unwrap_or(config.style);
+    let pin = &mut config.pin;
+    pin.leaf_type = args.leaf_type.or(pin.leaf_type);
+    pin.leaf_color = args.leaf_color.or(pin.leaf_color);
+    pin.leaf_count = args.leaf_count.map(usize::from).or(pin.leaf_count);
+    pin.base = args.base.or(pin.base);
+    config.trunk_color = args.trunk_color.unwrap_or(config.trunk_color);
+    if let Some(leaves) = &args.leaf_glyphs {
+        config.leaves.set_all(leaves);
+    }
+    if let Some(glyphs) = &args.trunk_glyphs {
+        config.trunk_glyphs = TrunkGlyphs::uniform(glyphs);
+    }
+    config
+}
+
+
+/// Grows the whole tree without delays (or loads the imported one),
+/// then prints and/or exports it
+fn run_headless(args: &Args, config: &Config) -> bonsai::Result<()> {
+    let canvas = match &args.import {
+        Some(path) => {
+            let document = load_document(path)?;
+            let mut canvas = Canvas::new(document.width as u16, document.height as u16);
+            document.render(&mut canvas)?;
+            canvas
+        },
+        None => {
+            let mut forest = grow_forest(args, config, headless_screen(args))?;
+            let mut canvas = Canvas::for_forest(&forest);
+            let (width, height) = (canvas.width(), canvas.height());
+            let mut cast = args.cast.as_ref().map(|_| AsciicastRecorder::new(width, height, args.time_scale));
+            let mut gif = args.gif.as_ref().map(|_| GifRecorder::new(width, height, args.time_scale, args.hold));
+            forest.grow_to_completion(&mut (&mut canvas, (cast.as_mut(), gif.as_mut())), MAX_GROWTH_STEPS)?;
+
+            if let (Some(path), Some(cast)) = (&args.cast, cast) {
+                cast.write(create_file(path)?)?;
+            }
+            if let (Some(path), Some(gif)) = (&args.gif, gif) {
+                gif.write(&raster_options(args), create_file(path)?)?;
+            }
+            if let Some(path) = &args.export {
+                // --count conflicts with --export, so there is only one tree
+                save_document(&TreeDocument::from_tree(&forest.trees()[0]), path)?;
+            }
+            canvas
+        },
+    };
+
+    if let Some(path) = &args.svg {
+        let options = svg::SvgOptions {
+            background: Some(args.background),
+            ..Default::default()
+        };
+        std::fs::write(path, svg::to_svg(&canvas, &options)).map_err(|err| with_path(err, path))?;
+    }
+    if let Some(path) = &args.png {
+        png::write_png(&canvas, &raster_options(args), create_file(path)?)?;
+    }
+    if args.print {
+        let text = match args.plain {
+            true => canvas.to_plain_string(),
+            false => canvas.to_ansi_string(),
+        };
+        // Unlike `print!` this doesn't panic when stdout is closed
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+
+fn raster_options(args: &Args) -> RasterOptions {
+    RasterOptions {
+        cell_width: args.cell_width,
+        cell_height: args.cell_height,
+        background: args.background,
+        ..Default::default()
+    }
+}
 
+
+fn create_file(path: &Path) -> bonsai::Result<std::io::BufWriter<std::fs::File>> {
+    let file = std::fs::File::create(path).map_err(|err| with_path(err, path))?;
+    Ok(std::io::BufWriter::new(file))
+}
+
+
+/// Adds the path to an I/O error, the message alone doesn't tell which file it is about
+fn with_path(err: std::io::Error, path: &Path) -> Error {
+    Error::Io(std::io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+}
+
+
+/// Blanks the part of the screen a tree grows in, leaving everything around it alone
+fn clear<W: Write>(out: &mut W, region: &Region) -> bonsai::Result<()> {
+    let blank = " ".repeat(region.width.max(0) as usize);
+    for y in region.y..region.bottom() {
+        queue!(out, cursor::MoveTo(region.x as u16, y as u16), crossterm::style::Print(&blank))?;
+    }
+    Ok(out.flush()?)
+}
+
+
+/// Shows an imported tree on the terminal until a key is pressed
+fn show_document(document: &TreeDocument) -> bonsai::Result<()> {
+    document.render(&mut TerminalRenderer::stdout())?;
+    while !matches!(read()?, Event::Key(_)) {}
+    Ok(())
+}
+
+
+/// Whether a document path should use RON instead of JSON
+fn is_ron(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "ron")
+}
+
+
+fn save_document(document: &TreeDocument, path: &Path) -> bonsai::Result<()> {
+    let contents = match is_ron(path) {
+        true => document.to_ron()?,
+        false => document.to_json()?,
+    };
+    std::fs::write(path, contents).map_err(|err| with_path(err, path))
+}
+
+
+fn load_document(path: &Path) -> bonsai::Result<TreeDocument> {
+    let contents = std::fs::read_to_string(path).map_err(|err| with_path(err, path))?;
+    match is_ron(path) {
+        true => TreeDocument::from_ron(&contents),
+        false => TreeDocument::from_json(&contents),
+    }
+}
+
+
+/// Sets up --count trees side by side. They get consecutive seeds, starting with --seed
+fn grow_forest(args: &Args, config: &Config, screen: (u16, u16)) -> bonsai::Result<Forest> {
     let mut seed = args.seed;
     if seed == 0 {
-        seed = rng.gen_range(0..MAX - 1);
+        seed = rand::thread_rng().gen_range(0..u64::MAX - 1);
     };
+    let trees = Forest::split(tree_region(args, screen), args.count as usize)?
+        .into_iter()
+        .enumerate()
+        .map(|(idx, region)| grow_bonsai(args, config, seed.wrapping_add(idx as u64), region))
+        .collect::<bonsai::Result<Vec<BonsaiTree>>>()?;
+    Ok(Forest::new(trees))
+}
+
+
+/// Sets up the growth of a new bonsai tree
+fn grow_bonsai(args: &Args, config: &Config, seed: u64, region: Region) -> bonsai::Result<BonsaiTree> {
     let mut rng = ChaCha8Rng::seed_from_u64(seed);
 
-    let freq = 4.0;
+    let freq = config.noise_frequency;
     let noise = NoiseConfig::new(
         1, // Octaves
         freq, // X-Frequency
@@ -103,19 +453,35 @@ fn grow_bonsai(args: Args, stdout: Writer) -> BonsaiTree {
         seed // seed
     );
 
-    let (width, height) = crossterm::terminal::size().unwrap();
     let mut trunk_width = args.width;
     if trunk_width == 0 {
-        trunk_width = rng.gen_range(5..15);
+        trunk_width = rng.gen_range(config.trunk_width.0..=config.trunk_width.1);
     };
 
-    BonsaiTree::new(
+    BonsaiTree::in_region(
         noise,
         rng,
         seed,
-        stdout,
-        width as i16,
-        height as i16,
-        trunk_width
+        region,
+        trunk_width,
+        config
     )
+}
+
+
+/// The screen trees grow on without a terminal. With --size it's just large enough for the region,
+/// so printed and exported trees aren't cut off by the size of the terminal (or the lack of one)
+fn headless_screen(args: &Args) -> (u16, u16) {
+    let (x, y) = args.offset.unwrap_or((0, 0));
+    match args.size {
+        Some((width, height)) => (x.saturating_add(width), y.saturating_add(height)),
+        None => crossterm::terminal::size().unwrap_or(FALLBACK_SIZE),
+    }
+}
+
+/// The part of the screen given by --offset and --size, cut off at the edges of the screen
+fn tree_region(args: &Args, (width, height): (u16, u16)) -> Region {
+    let (x, y) = args.offset.unwrap_or((0, 0));
+    let (region_width, region_height) = args.size.unwrap_or((width.saturating_sub(x), height.saturating_sub(y)));
+    Region::new(x as i16, y as i16, region_width as i16, region_height as i16).clamp_to(width as i16, height as i16)
 }
\ No newline at end of file
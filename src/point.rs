@@ -1,8 +1,10 @@
 use std::{fmt, ops::Add};
 
+use serde::{Deserialize, Serialize};
+
 
 /// Helpers struct to represent a 2D point
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Point<T: PartialEq + Ord> {
     pub x: T,
     pub y: T,
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+
+/// A rectangle of the screen a tree grows in, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    /// Column of the left edge
+    pub x: i16,
+    /// Row of the top edge
+    pub y: i16,
+    pub width: i16,
+    pub height: i16,
+}
+
+impl Region {
+    pub fn new(x: i16, y: i16, width: i16, height: i16) -> Region {
+        Region { x, y, width, height }
+    }
+
+    /// The whole screen of the given size
+    pub fn screen(width: i16, height: i16) -> Region {
+        Region::new(0, 0, width, height)
+    }
+
+    /// First column right of the region
+    pub fn right(&self) -> i16 {
+        self.x + self.width
+    }
+
+    /// First row below the region
+    pub fn bottom(&self) -> i16 {
+        self.y + self.height
+    }
+
+    /// Returns the part of the region which lies on a screen of the given size
+    pub fn clamp_to(&self, width: i16, height: i16) -> Region {
+        let x = self.x.clamp(0, width);
+        let y = self.y.clamp(0, height);
+        Region::new(x, y, self.width.min(width - x), self.height.min(height - y))
+    }
+}
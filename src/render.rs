@@ -0,0 +1,101 @@
+use std::io::Write;
+
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthChar;
+
+use crate::{draw, Result, Writer};
+
+
+/// Something a tree can draw itself onto.
+/// The tree and its branches emit every glyph through this trait, so growing
+/// a tree does not require a terminal.
+pub trait Renderer {
+    /// Draws `what` at the given (column, row) position with the given color
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()>;
+
+    /// Called once the tree has emitted all draw operations of a step
+    fn flush(&mut self) -> Result<()>;
+}
+
+impl<R: Renderer + ?Sized> Renderer for &mut R {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        (**self).draw(pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        (**self).flush()
+    }
+}
+
+/// Draws only if there is a renderer
+impl<R: Renderer> Renderer for Option<R> {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        match self {
+            Some(renderer) => renderer.draw(pos, what, color),
+            None => Ok(()),
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self {
+            Some(renderer) => renderer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Draws onto two renderers at once
+impl<A: Renderer, B: Renderer> Renderer for (A, B) {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        self.0.draw(pos, what, color)?;
+        self.1.draw(pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.0.flush()?;
+        self.1.flush()
+    }
+}
+
+
+
+/// Number of terminal cells a character covers, 2 for wide characters like most emoji.
+/// Characters without a width of their own (like combining marks) still get a cell
+pub fn char_width(symbol: char) -> usize {
+    symbol.width().unwrap_or(1).max(1)
+}
+
+
+/// Number of terminal cells `text` covers, see `char_width`
+pub fn text_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+
+/// Renders the tree into a terminal using crossterm escape sequences
+pub struct TerminalRenderer<W: Write = Writer> {
+    pub out: W,
+}
+
+impl<W: Write> TerminalRenderer<W> {
+    pub fn new(out: W) -> TerminalRenderer<W> {
+        TerminalRenderer { out }
+    }
+}
+
+impl TerminalRenderer<Writer> {
+    /// Creates a renderer which writes into a buffered stdout
+    pub fn stdout() -> TerminalRenderer<Writer> {
+        TerminalRenderer::new(std::io::BufWriter::new(std::io::stdout()))
+    }
+}
+
+impl<W: Write> Renderer for TerminalRenderer<W> {
+    fn draw(&mut self, pos: (u16, u16), what: &str, color: Color) -> Result<()> {
+        draw(&mut self.out, pos, what, color)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(self.out.flush()?)
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{IsTerminal, Write};
+
+use crossterm::{cursor, execute, terminal::{self, EnterAlternateScreen, LeaveAlternateScreen}};
+
+use crate::{Error, Result};
+
+
+/// Prepares the terminal for drawing a tree and restores it once dropped,
+/// also when the program panics
+pub struct TerminalSession {
+    alternate_screen: bool,
+}
+
+impl TerminalSession {
+    /// Enables raw mode and hides the cursor.
+    /// With `alternate_screen` the tree is drawn on the alternate screen, so the previous
+    /// terminal content comes back afterwards. Otherwise the tree stays visible after quitting.
+    /// Fails with `Error::NotATerminal` if stdout isn't a terminal
+    pub fn start(alternate_screen: bool) -> Result<TerminalSession> {
+        if !std::io::stdout().is_terminal() {
+            return Err(Error::NotATerminal);
+        }
+        // Restore the terminal before the panic message is printed, otherwise it gets lost on the alternate screen
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            restore(alternate_screen);
+            previous_hook(info);
+        }));
+
+        // Created first, so whatever fails below is undone as well
+        let session = TerminalSession { alternate_screen };
+        let mut stdout = std::io::stdout();
+        if alternate_screen {
+            execute!(stdout, EnterAlternateScreen)?;
+        }
+        terminal::enable_raw_mode()?;
+        execute!(stdout, cursor::Hide)?;
+        Ok(session)
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        restore(self.alternate_screen);
+    }
+}
+
+
+/// Undoes everything `TerminalSession::start` changed. Errors are ignored, there is nothing left to do about them
+fn restore(alternate_screen: bool) {
+    let mut stdout = std::io::stdout();
+    let _ = terminal::disable_raw_mode();
+    if alternate_screen {
+        let _ = execute!(stdout, LeaveAlternateScreen);
+    } else {
+        // Continue below the tree
+        let height = terminal::size().map_or(0, |(_, height)| height);
+        let _ = execute!(stdout, cursor::MoveTo(0, height.saturating_sub(1)));
+        let _ = writeln!(stdout);
+    }
+    let _ = execute!(stdout, cursor::Show);
+}
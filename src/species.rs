@@ -0,0 +1,108 @@
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{appearance::BaseType, branch::{Branching, BranchShape, LeafType, Style}, config::Config};
+
+
+/// Named presets for how a tree grows and looks, like a species of real bonsai
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Species {
+    /// Dense, round clusters of dark foliage on many short branches
+    Juniper,
+    /// Tall trunk with long, level branches and tufts of needles
+    Pine,
+    /// Broad crown of autumn leaves
+    Maple,
+    /// Long branches which hang down
+    WeepingWillow,
+    /// Branches pouring over one side of the pot
+    Cascade,
+    /// Straight trunk with a fan of short, steep branches at the top
+    Broom,
+}
+
+impl std::str::FromStr for Species {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, String> {
+        crate::parse_name(name)
+    }
+}
+
+impl Species {
+    /// Replaces the growth and leaf settings of `config` (shapes, branching, leaf types, colors and counts)
+    /// with the ones of the species, on top of the built-in defaults. Glyphs, the style and the base are
+    /// only changed by species which need their own. Pins are left alone, so they still take precedence
+    pub fn apply(&self, config: &mut Config) {
+        let defaults = Config::default();
+        config.trunk_shape = defaults.trunk_shape;
+        config.branch_shape = defaults.branch_shape;
+        config.branching = defaults.branching;
+        config.leaf_types = defaults.leaf_types;
+        config.leaf_colors = defaults.leaf_colors;
+        config.dark_leaf_chance = defaults.dark_leaf_chance;
+        config.leaf_count = defaults.leaf_count;
+        config.leaves_per_cluster = defaults.leaves_per_cluster;
+
+        let trunk = BranchShape::default_trunk();
+        let branch = BranchShape::default_branch();
+        let greens = vec![Color::Green, Color::DarkGreen];
+        match self {
+            Species::Juniper => {
+                config.branch_shape = BranchShape { min_width_loose_chance: 0.3, length: 8, rise_chance: 0.35, ..branch };
+                config.branching = Branching { start: 0.3, spacing: 0.8, frequency: 1.2, bias: 0.0 };
+                config.leaf_types = vec![LeafType::Round];
+                config.leaf_colors = vec![Color::DarkGreen, Color::Rgb { r: 46, g: 94, b: 60 }];
+                config.leaf_count = (2, 3);
+            },
+            Species::Pine => {
+                config.trunk_shape = BranchShape { min_width_loose_chance: 0.2, ..trunk };
+                config.branch_shape = BranchShape { length: 12, rise_chance: 0.15, droop_chance: 0.05, ..branch };
+                config.branching = Branching { start: 0.25, spacing: 1.5, frequency: 0.8, bias: 0.0 };
+                config.leaf_types = vec![LeafType::Needles];
+            },
+            Species::Maple => {
+                config.branch_shape = BranchShape { length: 11, rise_chance: 0.4, ..branch };
+                config.branching = Branching { start: 0.4, ..Branching::default() };
+                config.leaf_types = vec![LeafType::Maple];
+            },
+            Species::WeepingWillow => {
+                config.branch_shape = BranchShape { length: 14, rise_chance: 0.05, droop_chance: 0.4, ..branch };
+                config.branching = Branching { start: 0.5, frequency: 1.3, ..Branching::default() };
+                config.leaf_types = vec![LeafType::Pointy];
+                config.leaf_colors = vec![Color::Green, Color::Rgb { r: 150, g: 190, b: 80 }, Color::Rgb { r: 110, g: 160, b: 60 }];
+            },
+            Species::Cascade => {
+                config.branch_shape = BranchShape { length: 12, rise_chance: 0.05, droop_chance: 0.5, ..branch };
+                config.branching = Branching::default();
+                config.leaf_types = vec![LeafType::Round, LeafType::Pointy];
+                config.leaf_colors = greens;
+                config.style = Style::Cascade;
+                config.pin.base = config.pin.base.or(Some(BaseType::CascadePot));
+            },
+            Species::Broom => {
+                config.trunk_shape = BranchShape { min_width_loose_chance: 0.3, ..trunk };
+                config.style = Style::FormalUpright;
+                config.branch_shape = BranchShape { length: 6, rise_chance: 0.6, ..branch };
+                config.branching = Branching { start: 0.55, spacing: 0.5, frequency: 1.6, bias: 0.0 };
+                config.leaf_types = vec![LeafType::Round];
+                config.leaf_colors = greens;
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_the_ones_of_config_files() {
+        assert_eq!("weeping-willow".parse(), Ok(Species::WeepingWillow));
+        assert!(matches!("footed-pot".parse(), Ok(BaseType::FootedPot)));
+        let error = "oak".parse::<Species>().unwrap_err();
+        assert!(error.contains("`juniper`") && error.contains("`broom`"), "{}", error);
+    }
+}
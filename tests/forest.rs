@@ -0,0 +1,49 @@
+use bonsai::{config::Config, forest::Forest, region::Region, render::{text_width, Renderer}, BonsaiTree, MAX_GROWTH_STEPS};
+use crossterm::style::Color;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use simple_simplex::NoiseConfig;
+
+
+/// Fails the test when something is drawn outside of the region
+struct RegionCheck(Region);
+
+impl Renderer for RegionCheck {
+    fn draw(&mut self, pos: (u16, u16), what: &str, _color: Color) -> bonsai::Result<()> {
+        let (x, y) = (pos.0 as i16, pos.1 as i16);
+        let right = x + text_width(what) as i16;
+        assert!(
+            x >= self.0.x && right <= self.0.right() && y >= self.0.y && y < self.0.bottom(),
+            "{:?} drawn at {:?} outside of {:?}", what, pos, self.0
+        );
+        Ok(())
+    }
+
+    fn flush(&mut self) -> bonsai::Result<()> {
+        Ok(())
+    }
+}
+
+
+#[test]
+fn trees_stay_in_their_regions() {
+    let config = Config::default();
+    for (size, count) in [((80, 20), 3), ((80, 20), 4), ((100, 40), 2), ((64, 11), 4)] {
+        let regions = Forest::split(Region::new(3, 2, size.0, size.1), count).unwrap();
+        for (idx, &region) in regions.iter().cycle().take(40).enumerate() {
+            // Long seeds make long captions
+            let seed = u64::MAX - idx as u64 - 1000 * size.1 as u64;
+            let noise = NoiseConfig::new(1, 4.0, 4.0, 2., 2.5, 0.7, (-2.0, 2.0), seed);
+            let mut tree = BonsaiTree::in_region(noise, ChaCha8Rng::seed_from_u64(seed), seed, region, 9, &config).unwrap();
+            let mut check = RegionCheck(region);
+            assert!(tree.grow_to_completion(&mut check, MAX_GROWTH_STEPS).unwrap());
+            tree.redraw(&mut check).unwrap();
+        }
+    }
+}
+
+#[test]
+fn regions_narrower_than_the_smallest_tree_are_refused() {
+    assert_eq!(Forest::split(Region::new(0, 0, 80, 20), 5).unwrap().len(), 5);
+    assert!(matches!(Forest::split(Region::new(0, 0, 80, 20), 6), Err(bonsai::Error::TerminalTooSmall { .. })));
+}
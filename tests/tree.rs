@@ -0,0 +1,101 @@
+use bonsai::{canvas::Canvas, config::Config, document::TreeDocument, species::Species, BonsaiTree, Error, MAX_GROWTH_STEPS};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use simple_simplex::NoiseConfig;
+
+
+/// Grows the tree `bonsai --print --seed <seed>` grows on an 80x24 terminal
+fn grow(seed: u64) -> (BonsaiTree, Canvas) {
+    grow_with(seed, &Config::default())
+}
+
+/// Grows a tree like [`grow`] with the settings of `config`
+fn grow_with(seed: u64, config: &Config) -> (BonsaiTree, Canvas) {
+    let mut tree = plant(seed, config).unwrap();
+    let mut canvas = Canvas::for_tree(&tree);
+    assert!(tree.grow_to_completion(&mut canvas, MAX_GROWTH_STEPS).unwrap());
+    (tree, canvas)
+}
+
+/// Creates the tree [`grow_with`] grows, before its first step
+fn plant(seed: u64, config: &Config) -> bonsai::Result<BonsaiTree> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let freq = config.noise_frequency;
+    let noise = NoiseConfig::new(1, freq, freq, 2., 2.5, 0.7, (-2.0, 2.0), seed);
+    let trunk_width = rng.gen_range(config.trunk_width.0..=config.trunk_width.1);
+    BonsaiTree::in_region(noise, rng, seed, bonsai::region::Region::screen(80, 24), trunk_width, config)
+}
+
+
+#[test]
+fn grows_the_same_tree_for_the_same_seed() {
+    for (seed, expected) in [(42, include_str!("golden/seed_42.txt")), (7, include_str!("golden/seed_7.txt"))] {
+        let (_, canvas) = grow(seed);
+        assert_eq!(canvas.to_plain_string(), expected, "seed {}", seed);
+    }
+}
+
+#[test]
+fn redraw_paints_the_grown_picture() {
+    for seed in 1..20 {
+        let (tree, grown) = grow(seed);
+        let mut redrawn = Canvas::for_tree(&tree);
+        tree.redraw(&mut redrawn).unwrap();
+        assert_eq!(redrawn.to_ansi_string(), grown.to_ansi_string(), "seed {}", seed);
+    }
+}
+
+#[test]
+fn imported_documents_look_like_the_exported_tree() {
+    for seed in 1..10 {
+        let (tree, grown) = grow(seed);
+        let document = TreeDocument::from_tree(&tree);
+        for imported in [
+            TreeDocument::from_json(&document.to_json().unwrap()).unwrap(),
+            TreeDocument::from_ron(&document.to_ron().unwrap()).unwrap(),
+        ] {
+            let mut canvas = Canvas::for_tree(&tree);
+            imported.render(&mut canvas).unwrap();
+            assert_eq!(canvas.to_ansi_string(), grown.to_ansi_string(), "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn foliage_leaves_the_caption_alone() {
+    let species = [Species::Juniper, Species::Pine, Species::Maple, Species::WeepingWillow, Species::Cascade, Species::Broom];
+    for species in species {
+        let mut config = Config::default();
+        species.apply(&mut config);
+        for seed in 1..20 {
+            let (_, canvas) = grow_with(seed, &config);
+            let picture = canvas.to_plain_string();
+            let caption = picture.lines().last().unwrap_or_default();
+            assert_eq!(caption.trim(), format!("Seed: {}", seed), "{:?}, seed {}", species, seed);
+        }
+    }
+}
+
+#[test]
+fn invalid_configs_are_refused() {
+    let mut config = Config::default();
+    config.leaf_types.clear();
+    assert!(matches!(plant(1, &config), Err(Error::InvalidConfig(_))));
+
+    let config = Config { leaf_count: (4, 2), ..Config::default() };
+    assert!(matches!(plant(1, &config), Err(Error::InvalidConfig(_))));
+}
+
+#[test]
+fn documents_which_cant_be_drawn_are_rejected() {
+    let (tree, _) = grow(1);
+    let mut document = TreeDocument::from_tree(&tree);
+    document.branches[0].steps.clear();
+    let result = TreeDocument::from_json(&document.to_json().unwrap());
+    assert!(matches!(result, Err(Error::Document(_))));
+
+    let mut document = TreeDocument::from_tree(&tree);
+    document.width = -5;
+    let result = TreeDocument::from_json(&document.to_json().unwrap());
+    assert!(matches!(result, Err(Error::Document(_))));
+}